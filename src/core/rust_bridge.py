@@ -205,7 +205,8 @@ class RustRace:
         self._turtles = []
         
         if RUST_AVAILABLE:
-            self._rust = turboshells_core.PyRace(track_length)
+            # The fallback below stops at the first finisher; match it
+            self._rust = turboshells_core.PyRace(track_length, finish_mode="first")
             self._use_rust = True
         else:
             self._use_rust = False
@@ -216,8 +217,8 @@ class RustRace:
         if self._use_rust and turtle._use_rust:
             self._rust.add_turtle(turtle._rust)
     
-    def run(self) -> str:
-        """Run the full race. Returns winner name."""
+    def run(self) -> "str | None":
+        """Run the full race. Returns winner name, or None with no turtles."""
         if self._use_rust:
             return self._rust.run()
         else:
@@ -235,6 +236,8 @@ class RustRace:
                             return t.name
             
             # Find furthest
+            if not self._turtles:
+                return None
             return max(self._turtles, key=lambda t: t.race_distance).name
     
     def tick(self) -> bool:
@@ -0,0 +1,145 @@
+//! Compact binary encoding of genomes for bulk storage
+//!
+//! Layout (integers little-endian):
+//!
+//! | Bytes | Field                                      |
+//! |-------|--------------------------------------------|
+//! | 3     | magic `TSG`                                |
+//! | 1     | format version (currently 1)               |
+//! | 2     | entry count                                |
+//! | ...   | entries                                    |
+//!
+//! Each entry is `name_len: u8, name, tag: u8, payload_len: u8, payload`,
+//! with tag 0 = rgb (`r, g, b`), 1 = continuous (the f32's raw bits) and
+//! 2 = discrete (UTF-8 text). Entries follow the registry's sorted gene
+//! order, then any unregistered genes by name. Because every payload is
+//! length-prefixed, a reader skips entries with tags it does not know and
+//! ignores bytes after the last entry, so later format versions can add
+//! data without breaking older readers.
+
+use std::collections::HashMap;
+use crate::types::{GeneValue, Rgb};
+use super::genes::GeneDefinitions;
+
+const MAGIC: &[u8; 3] = b"TSG";
+const FORMAT_VERSION: u8 = 1;
+
+const TAG_RGB: u8 = 0;
+const TAG_CONTINUOUS: u8 = 1;
+const TAG_DISCRETE: u8 = 2;
+
+/// Encode a genome; fails if a name or discrete value exceeds 255 bytes
+/// or there are more than 65535 genes
+pub fn to_bytes(genetics: &HashMap<String, GeneValue>, definitions: &GeneDefinitions) -> Result<Vec<u8>, String> {
+    let mut names: Vec<&String> = definitions.names()
+        .into_iter()
+        .filter(|name| genetics.contains_key(*name))
+        .collect();
+    let mut extras: Vec<&String> = genetics.keys()
+        .filter(|name| definitions.get(name).is_none())
+        .collect();
+    extras.sort();
+    names.extend(extras);
+    
+    let count = u16::try_from(names.len())
+        .map_err(|_| format!("Too many genes to encode: {}", names.len()))?;
+    let mut bytes = Vec::with_capacity(6 + names.len() * 24);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(FORMAT_VERSION);
+    bytes.extend_from_slice(&count.to_le_bytes());
+    
+    for name in names {
+        let (tag, payload) = match &genetics[name] {
+            GeneValue::Rgb(rgb) => (TAG_RGB, vec![rgb.r, rgb.g, rgb.b]),
+            GeneValue::Continuous(f) => (TAG_CONTINUOUS, f.to_bits().to_le_bytes().to_vec()),
+            GeneValue::Discrete(s) => (TAG_DISCRETE, s.as_bytes().to_vec()),
+        };
+        push_short(&mut bytes, name.as_bytes(), name)?;
+        bytes.push(tag);
+        push_short(&mut bytes, &payload, name)?;
+    }
+    Ok(bytes)
+}
+
+/// Decode bytes written by `to_bytes`
+pub fn from_bytes(bytes: &[u8]) -> Result<HashMap<String, GeneValue>, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(3)? != MAGIC {
+        return Err("Not a genetics blob (bad magic)".to_string());
+    }
+    let _version = reader.take(1)?[0];
+    let count = reader.take(2)?;
+    let count = u16::from_le_bytes([count[0], count[1]]);
+    
+    let mut genetics = HashMap::with_capacity(count as usize);
+    for _ in 0..count {
+        let name = reader.short()?;
+        let name = std::str::from_utf8(name)
+            .map_err(|_| "Gene name is not valid UTF-8".to_string())?
+            .to_string();
+        let tag = reader.take(1)?[0];
+        let payload = reader.short()?;
+        let value = match (tag, payload) {
+            (TAG_RGB, &[r, g, b]) => GeneValue::Rgb(Rgb::new(r, g, b)),
+            (TAG_CONTINUOUS, &[a, b, c, d]) => GeneValue::Continuous(f32::from_bits(u32::from_le_bytes([a, b, c, d]))),
+            (TAG_DISCRETE, text) => GeneValue::Discrete(
+                std::str::from_utf8(text)
+                    .map_err(|_| format!("Value of '{}' is not valid UTF-8", name))?
+                    .to_string(),
+            ),
+            (TAG_RGB | TAG_CONTINUOUS, _) => return Err(format!("Malformed value for '{}'", name)),
+            // Written by a newer format; skip it
+            _ => continue,
+        };
+        genetics.insert(name, value);
+    }
+    Ok(genetics)
+}
+
+/// Append a u8 length prefix and the data
+fn push_short(bytes: &mut Vec<u8>, data: &[u8], gene: &str) -> Result<(), String> {
+    let len = u8::try_from(data.len())
+        .map_err(|_| format!("'{}' is too long to encode", gene))?;
+    bytes.push(len);
+    bytes.extend_from_slice(data);
+    Ok(())
+}
+
+/// Cursor over an encoded blob
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos + n;
+        let slice = self.bytes.get(self.pos..end)
+            .ok_or_else(|| "Genetics blob is truncated".to_string())?;
+        self.pos = end;
+        Ok(slice)
+    }
+    
+    /// Read a u8 length prefix and that many bytes
+    fn short(&mut self) -> Result<&'a [u8], String> {
+        let len = self.take(1)?[0] as usize;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    
+    #[test]
+    fn binary_round_trips_and_beats_json() {
+        let definitions = GeneDefinitions::new();
+        let genetics = definitions.generate_random_with(&mut rng::seeded(11));
+        let bytes = to_bytes(&genetics, &definitions).unwrap();
+        assert_eq!(from_bytes(&bytes).unwrap(), genetics);
+        
+        let json = serde_json::to_vec(&genetics).unwrap();
+        assert!(bytes.len() < json.len(), "binary {} vs json {}", bytes.len(), json.len());
+    }
+}
@@ -0,0 +1,201 @@
+//! Population diversity and heritability metrics
+
+use std::collections::{BTreeMap, HashMap};
+use rayon::prelude::*;
+use crate::types::{GeneValue, Rgb};
+use super::genes::GeneDefinitions;
+use super::inheritance::Inheritance;
+
+type Genome = HashMap<String, GeneValue>;
+
+/// Mean of `1 - similarity` over all unique pairs (0.0 for fewer than two)
+pub fn population_diversity(population: &[HashMap<String, GeneValue>], inheritance: &Inheritance) -> f32 {
+    let n = population.len();
+    if n < 2 {
+        return 0.0;
+    }
+    
+    let total: f32 = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            population[i + 1..]
+                .iter()
+                .map(|other| 1.0 - inheritance.calculate_similarity(&population[i], other))
+                .sum::<f32>()
+        })
+        .sum();
+    let pairs = (n * (n - 1) / 2) as f32;
+    total / pairs
+}
+
+/// Similarity of `query` to each genome in `others`, in order
+/// Comparisons run in parallel; each result equals the per-pair
+/// `calculate_similarity`
+pub fn similarity_to_all(query: &Genome, others: &[Genome], inheritance: &Inheritance) -> Vec<f32> {
+    others.par_iter()
+        .map(|g| inheritance.calculate_similarity(query, g))
+        .collect()
+}
+
+/// Spread of each registered gene across a population
+///
+/// Continuous genes report their variance, rgb genes the mean variance of
+/// their channels scaled to [0, 1], and discrete genes the Shannon entropy
+/// (in bits) of their allele frequencies. Missing genes count as defaults.
+pub fn gene_spread(population: &[HashMap<String, GeneValue>], definitions: &GeneDefinitions) -> BTreeMap<String, f32> {
+    let mut spread = BTreeMap::new();
+    if population.is_empty() {
+        return spread;
+    }
+    
+    for name in definitions.names() {
+        let default = &definitions.get(name).unwrap().default;
+        let values: Vec<&GeneValue> = population.iter()
+            .map(|genetics| genetics.get(name).unwrap_or(default))
+            .collect();
+        
+        let value = match default {
+            GeneValue::Continuous(_) => {
+                variance(values.iter().filter_map(|v| v.as_continuous()))
+            },
+            GeneValue::Rgb(_) => {
+                let colors: Vec<_> = values.iter().filter_map(|v| v.as_rgb()).collect();
+                let channel = |f: fn(&Rgb) -> u8| {
+                    variance(colors.iter().map(|c| f(c) as f32 / 255.0))
+                };
+                (channel(|c| c.r) + channel(|c| c.g) + channel(|c| c.b)) / 3.0
+            },
+            GeneValue::Discrete(_) => {
+                let mut counts: HashMap<&str, usize> = HashMap::new();
+                for allele in values.iter().filter_map(|v| v.as_discrete()) {
+                    *counts.entry(allele).or_default() += 1;
+                }
+                let total = counts.values().sum::<usize>() as f32;
+                counts.values()
+                    .map(|&count| {
+                        let p = count as f32 / total;
+                        -p * p.log2()
+                    })
+                    .sum()
+            },
+        };
+        spread.insert(name.clone(), value);
+    }
+    
+    spread
+}
+
+/// Correlation between mid-parent and child value of each continuous gene
+///
+/// `children[i]` must be bred from `parents[i]`. A correlation near 1.0
+/// means children track their parents closely (blended inheritance);
+/// picking one parent's value gives less. Pairs where any of the three
+/// lacks the gene are skipped, and genes left with fewer than two samples
+/// or no variation in either series are omitted.
+pub fn heritability(
+    parents: &[(Genome, Genome)],
+    children: &[Genome],
+    definitions: &GeneDefinitions,
+) -> BTreeMap<String, f32> {
+    let mut result = BTreeMap::new();
+    for name in definitions.names() {
+        if !matches!(definitions.get(name).unwrap().default, GeneValue::Continuous(_)) {
+            continue;
+        }
+        let samples: Vec<(f32, f32)> = parents.iter()
+            .zip(children)
+            .filter_map(|((p1, p2), child)| {
+                let p1 = p1.get(name)?.as_continuous()?;
+                let p2 = p2.get(name)?.as_continuous()?;
+                Some(((p1 + p2) / 2.0, child.get(name)?.as_continuous()?))
+            })
+            .collect();
+        if let Some(r) = correlation(&samples) {
+            result.insert(name.clone(), r);
+        }
+    }
+    result
+}
+
+/// Pearson correlation of `(x, y)` samples, clamped to [-1, 1]
+/// None for fewer than two samples or when either series is constant
+fn correlation(samples: &[(f32, f32)]) -> Option<f32> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for &(x, y) in samples {
+        let (dx, dy) = (x as f64 - mean_x, y as f64 - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    Some((cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0) as f32)
+}
+
+/// Population variance of a set of values (0.0 when empty)
+fn variance(values: impl Iterator<Item = f32>) -> f32 {
+    let values: Vec<f32> = values.collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::rng;
+    
+    fn population(definitions: &GeneDefinitions, count: usize) -> Vec<Genome> {
+        let mut rng = rng::seeded(3);
+        (0..count).map(|_| definitions.generate_random_with(&mut rng)).collect()
+    }
+    
+    #[test]
+    fn similarity_to_all_matches_per_pair_and_is_deterministic() {
+        let definitions = GeneDefinitions::new();
+        let inheritance = Inheritance::new(definitions.clone());
+        let others = population(&definitions, 200);
+        let query = &others[0];
+        
+        let all = similarity_to_all(query, &others, &inheritance);
+        let per_pair: Vec<f32> = others.iter()
+            .map(|g| inheritance.calculate_similarity(query, g))
+            .collect();
+        assert_eq!(all, per_pair);
+        assert_eq!(all, similarity_to_all(query, &others, &inheritance));
+        assert_eq!(all[0], 1.0);
+    }
+    
+    /// `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_similarity_to_all() {
+        let definitions = GeneDefinitions::new();
+        let inheritance = Inheritance::new(definitions.clone());
+        let others = population(&definitions, 20_000);
+        let query = &others[0];
+        
+        let start = Instant::now();
+        let per_pair: Vec<f32> = others.iter()
+            .map(|g| inheritance.calculate_similarity(query, g))
+            .collect();
+        let serial = start.elapsed();
+        let start = Instant::now();
+        let all = similarity_to_all(query, &others, &inheritance);
+        let parallel = start.elapsed();
+        
+        assert_eq!(all, per_pair);
+        println!("{} genomes: per-pair {:?}, similarity_to_all {:?}", others.len(), serial, parallel);
+    }
+}
@@ -0,0 +1,73 @@
+//! Generational evolution - selection, breeding and mutation in one pass
+
+use std::collections::HashMap;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use crate::types::GeneValue;
+use super::inheritance::Inheritance;
+use super::mutation::{Mutation, DEFAULT_MACRO_CHANCE};
+
+/// Breed the next generation from a scored population
+///
+/// The top `survivors` by fitness (ties keep input order) carry over
+/// unchanged. The rest of the population is refilled with mutated
+/// Mendelian offspring of two distinct survivors, each picked by roulette
+/// selection: chance proportional to fitness, shifted up by the lowest
+/// survivor fitness when any is negative, and uniform when every weight is
+/// zero. A lone survivor breeds with itself. Every draw comes from `rng`,
+/// so a seeded stream gives the same generation.
+pub fn evolve_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    inheritance: &Inheritance,
+    mutation: &Mutation,
+    population: &[HashMap<String, GeneValue>],
+    fitness: &[f32],
+    survivors: usize,
+    mutation_rate: f32,
+) -> Result<Vec<HashMap<String, GeneValue>>, String> {
+    if fitness.len() != population.len() {
+        return Err(format!("Got {} fitness scores for {} genomes", fitness.len(), population.len()));
+    }
+    if fitness.iter().any(|f| !f.is_finite()) {
+        return Err("Fitness scores must be finite".to_string());
+    }
+    if population.is_empty() {
+        return Ok(Vec::new());
+    }
+    if survivors == 0 || survivors > population.len() {
+        return Err(format!("survivors must be between 1 and {}, got {}", population.len(), survivors));
+    }
+    
+    let mut ranked: Vec<usize> = (0..population.len()).collect();
+    ranked.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    ranked.truncate(survivors);
+    
+    let lowest = ranked.iter().map(|&i| fitness[i]).fold(f32::INFINITY, f32::min);
+    let shift = if lowest < 0.0 { -lowest } else { 0.0 };
+    let weights: Vec<f32> = ranked.iter().map(|&i| fitness[i] + shift).collect();
+    
+    let mut next: Vec<HashMap<String, GeneValue>> = ranked.iter().map(|&i| population[i].clone()).collect();
+    while next.len() < population.len() {
+        let first = roulette(rng, &weights, None);
+        let second = if survivors > 1 { roulette(rng, &weights, Some(first)) } else { first };
+        let child = inheritance.inherit_with(rng, &population[ranked[first]], &population[ranked[second]]);
+        next.push(mutation.mutate_with(rng, &child, mutation_rate, DEFAULT_MACRO_CHANCE));
+    }
+    Ok(next)
+}
+
+/// Index drawn in proportion to `weights`, never `exclude`; uniform over
+/// the allowed indices when their weights are all zero
+fn roulette<R: Rng + ?Sized>(rng: &mut R, weights: &[f32], exclude: Option<usize>) -> usize {
+    let allowed: Vec<f32> = weights.iter()
+        .enumerate()
+        .map(|(i, &w)| if Some(i) == exclude { 0.0 } else { w })
+        .collect();
+    match WeightedIndex::new(&allowed) {
+        Ok(dist) => dist.sample(rng),
+        Err(_) => {
+            let candidates: Vec<usize> = (0..weights.len()).filter(|&i| Some(i) != exclude).collect();
+            candidates[rng.gen_range(0..candidates.len())]
+        },
+    }
+}
@@ -1,11 +1,49 @@
 //! Gene definitions - central registry of all genetic traits
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::rng;
 use crate::types::{GeneValue, Rgb};
 
+/// Step continuous genes are rounded to in canonical form
+pub const CONTINUOUS_PRECISION: f32 = 1e-4;
+
+/// Default std dev of a continuous mutation, as a fraction of the range
+pub const DEFAULT_MUTATION_STRENGTH: f32 = 0.1;
+
+fn default_mutation_strength() -> f32 {
+    DEFAULT_MUTATION_STRENGTH
+}
+
+/// FNV-1a 64-bit parameters used for fingerprints and registry hashes
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Incremental FNV-1a 64-bit hash
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+    
+    fn feed(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Round a continuous value to `CONTINUOUS_PRECISION`
+fn quantize(value: f32) -> f32 {
+    (value / CONTINUOUS_PRECISION).round() * CONTINUOUS_PRECISION
+}
+
 /// Definition of a single gene
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GeneDefinition {
     pub gene_type: String,  // "rgb", "discrete", "continuous"
     pub default: GeneValue,
@@ -13,6 +51,19 @@ pub struct GeneDefinition {
     // Range data
     pub discrete_options: Option<Vec<String>>,
     pub continuous_range: Option<(f32, f32)>,
+    /// Mutation chance overriding the rate passed to `mutate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mutation_rate: Option<f32>,
+    /// Discrete alleles from most to least dominant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dominance: Option<Vec<String>>,
+    /// Std dev of a continuous mutation as a fraction of the range
+    #[serde(default = "default_mutation_strength")]
+    pub continuous_mutation_strength: f32,
+    /// Relative odds of each discrete option, parallel to
+    /// `discrete_options`; None means uniform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discrete_weights: Option<Vec<f32>>,
 }
 
 impl GeneDefinition {
@@ -23,6 +74,10 @@ impl GeneDefinition {
             description: description.to_string(),
             discrete_options: None,
             continuous_range: None,
+            mutation_rate: None,
+            dominance: None,
+            continuous_mutation_strength: DEFAULT_MUTATION_STRENGTH,
+            discrete_weights: None,
         }
     }
     
@@ -33,6 +88,50 @@ impl GeneDefinition {
             description: description.to_string(),
             discrete_options: Some(options.iter().map(|s| s.to_string()).collect()),
             continuous_range: None,
+            mutation_rate: None,
+            dominance: None,
+            continuous_mutation_strength: DEFAULT_MUTATION_STRENGTH,
+            discrete_weights: None,
+        }
+    }
+    
+    /// Rank of a discrete allele (0 = most dominant); unranked alleles
+    /// sit below every ranked one
+    pub fn dominance_rank(&self, allele: &str) -> Option<usize> {
+        let order = self.dominance.as_ref()?;
+        Some(order.iter().position(|a| a == allele).unwrap_or(order.len()))
+    }
+    
+    /// Draw a discrete option in proportion to `discrete_weights`
+    ///
+    /// `exclude` (the current value, when mutating) is never drawn.
+    /// Uniform when there are no weights (or their count doesn't match the
+    /// options) or every remaining weight is 0; None if no option is left.
+    pub fn pick_option<R: Rng + ?Sized>(&self, rng: &mut R, exclude: Option<&str>) -> Option<String> {
+        let options = self.discrete_options.as_ref()?;
+        let candidates: Vec<usize> = (0..options.len())
+            .filter(|&i| Some(options[i].as_str()) != exclude)
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let weighted = self.discrete_weights.as_ref()
+            .filter(|weights| weights.len() == options.len())
+            .and_then(|weights| WeightedIndex::new(candidates.iter().map(|&i| weights[i])).ok());
+        let pick = match weighted {
+            Some(dist) => candidates[dist.sample(rng)],
+            None => candidates[rng.gen_range(0..candidates.len())],
+        };
+        Some(options[pick].clone())
+    }
+    
+    /// Bring a value back inside this gene's bounds: continuous values are
+    /// clamped to the range, unknown discrete options become the default
+    pub fn sanitize(&self, value: GeneValue) -> GeneValue {
+        match (value, &self.continuous_range, &self.discrete_options) {
+            (GeneValue::Continuous(f), Some((min, max)), _) => GeneValue::Continuous(f.clamp(*min, *max)),
+            (GeneValue::Discrete(s), _, Some(options)) if !options.contains(&s) => self.default.clone(),
+            (value, _, _) => value,
         }
     }
     
@@ -43,14 +142,62 @@ impl GeneDefinition {
             description: description.to_string(),
             discrete_options: None,
             continuous_range: Some(range),
+            mutation_rate: None,
+            dominance: None,
+            continuous_mutation_strength: DEFAULT_MUTATION_STRENGTH,
+            discrete_weights: None,
+        }
+    }
+}
+
+/// How to resolve a gene defined differently in two merged registries
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictPolicy {
+    /// Keep the existing definition
+    Keep,
+    /// Replace it with the incoming definition
+    Overwrite,
+    /// Refuse the merge
+    Error,
+}
+
+impl ConflictPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "keep" => Some(ConflictPolicy::Keep),
+            "overwrite" => Some(ConflictPolicy::Overwrite),
+            "error" => Some(ConflictPolicy::Error),
+            _ => None,
         }
     }
 }
 
+/// Serialized form of a registry
+#[derive(Serialize, Deserialize)]
+struct Schema {
+    version: u32,
+    genes: BTreeMap<String, GeneDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    linkage: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lethal: Vec<BTreeMap<String, String>>,
+}
+
 /// Central registry of all gene definitions
 #[derive(Clone, Debug)]
 pub struct GeneDefinitions {
     definitions: HashMap<String, GeneDefinition>,
+    version: u32,
+    /// Groups of genes inherited together from the same parent
+    linkage: Vec<Vec<String>>,
+    /// Discrete gene=value sets that make a genome non-viable
+    lethal: Vec<BTreeMap<String, String>>,
+}
+
+impl Default for GeneDefinitions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GeneDefinitions {
@@ -58,7 +205,7 @@ impl GeneDefinitions {
         let mut definitions = HashMap::new();
         
         // Shell Genetics
-        definitions.insert("shell_base_color".to_string(), 
+        definitions.insert("shell_base_color".to_string(),
             GeneDefinition::rgb(Rgb::new(34, 139, 34), "Primary shell color"));
         definitions.insert("shell_pattern_type".to_string(),
             GeneDefinition::discrete(vec!["hex", "spots", "stripes", "rings"], "hex", "Shell pattern type"));
@@ -105,15 +252,251 @@ impl GeneDefinitions {
         definitions.insert("eye_size_modifier".to_string(),
             GeneDefinition::continuous((0.8, 1.2), 1.0, "Eye size scaling"));
         
-        Self { definitions }
+        Self { definitions, version: 1, linkage: Vec::new(), lethal: Vec::new() }
+    }
+    
+    /// Registry schema version, bumped whenever the gene set changes
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    
+    /// Export the registry as a JSON schema (genes in sorted order)
+    pub fn to_schema_json(&self) -> serde_json::Result<String> {
+        let schema = Schema {
+            version: self.version,
+            genes: self.definitions.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            linkage: self.linkage.clone(),
+            lethal: self.lethal.clone(),
+        };
+        serde_json::to_string(&schema)
+    }
+    
+    /// Load a registry from a JSON schema produced by `to_schema_json`
+    pub fn from_schema_json(json: &str) -> serde_json::Result<Self> {
+        let schema: Schema = serde_json::from_str(json)?;
+        Ok(Self {
+            definitions: schema.genes.into_iter().collect(),
+            version: schema.version,
+            linkage: schema.linkage,
+            lethal: schema.lethal,
+        })
+    }
+    
+    /// Merge another registry's genes into this one
+    ///
+    /// Genes defined identically in both are not conflicts. Under
+    /// `ConflictPolicy::Error` nothing is merged and the first conflicting
+    /// gene name is returned. The version becomes one past the newer of
+    /// the two registries.
+    pub fn merge(&mut self, other: &GeneDefinitions, policy: ConflictPolicy) -> Result<(), String> {
+        if policy == ConflictPolicy::Error {
+            if let Some(name) = other.names().into_iter().find(|name| {
+                self.definitions.get(*name).is_some_and(|def| def != &other.definitions[*name])
+            }) {
+                return Err(name.clone());
+            }
+        }
+        
+        for (name, def) in &other.definitions {
+            if policy == ConflictPolicy::Keep && self.definitions.contains_key(name) {
+                continue;
+            }
+            self.definitions.insert(name.clone(), def.clone());
+        }
+        for group in &other.linkage {
+            self.link(group.clone())?;
+        }
+        for combo in &other.lethal {
+            if !self.lethal.contains(combo) {
+                self.lethal.push(combo.clone());
+            }
+        }
+        
+        self.version = self.version.max(other.version) + 1;
+        Ok(())
     }
     
     pub fn get(&self, name: &str) -> Option<&GeneDefinition> {
         self.definitions.get(name)
     }
     
+    /// Link genes so they are inherited from the same parent
+    ///
+    /// Genes already in another group move to the new one. Returns the
+    /// first unregistered name as an error without changing anything.
+    pub fn link(&mut self, group: Vec<String>) -> Result<(), String> {
+        if let Some(name) = group.iter().find(|name| !self.definitions.contains_key(*name)) {
+            return Err(name.clone());
+        }
+        for existing in &mut self.linkage {
+            existing.retain(|name| !group.contains(name));
+        }
+        self.linkage.retain(|existing| existing.len() > 1);
+        self.linkage.push(group);
+        Ok(())
+    }
+    
+    /// Index of the linkage group containing `name`, if any
+    pub fn linkage_group(&self, name: &str) -> Option<usize> {
+        self.linkage.iter().position(|group| group.iter().any(|g| g == name))
+    }
+    
+    /// Number of linkage groups
+    pub fn linkage_count(&self) -> usize {
+        self.linkage.len()
+    }
+    
+    /// Record a lethal combination of discrete gene values
+    ///
+    /// Only discrete genes participate: every gene must be registered as
+    /// discrete and every value one of its options, otherwise nothing is
+    /// recorded and the problem is returned. An empty combo is rejected,
+    /// since it would match every genome.
+    pub fn add_lethal_combo(&mut self, combo: BTreeMap<String, String>) -> Result<(), String> {
+        if combo.is_empty() {
+            return Err("A lethal combo needs at least one gene".to_string());
+        }
+        for (name, value) in &combo {
+            let def = self.definitions.get(name)
+                .ok_or_else(|| format!("Unknown gene '{}'", name))?;
+            let options = match (def.gene_type.as_str(), &def.discrete_options) {
+                ("discrete", Some(options)) => options,
+                _ => return Err(format!("Gene '{}' is not discrete", name)),
+            };
+            if !options.contains(value) {
+                return Err(format!("'{}' is not an option of gene '{}'", value, name));
+            }
+        }
+        if !self.lethal.contains(&combo) {
+            self.lethal.push(combo);
+        }
+        Ok(())
+    }
+    
+    /// Whether a genome avoids every lethal combo
+    ///
+    /// A combo matches when each of its genes has the listed value; genes
+    /// missing from the genome count as their default.
+    pub fn is_viable(&self, genetics: &HashMap<String, GeneValue>) -> bool {
+        !self.lethal.iter().any(|combo| {
+            combo.iter().all(|(name, value)| {
+                let actual = genetics.get(name)
+                    .or_else(|| self.definitions.get(name).map(|def| &def.default));
+                actual.and_then(GeneValue::as_discrete) == Some(value.as_str())
+            })
+        })
+    }
+    
+    /// Add a gene, or overwrite an existing one of the same name
+    /// Bumps the registry version
+    pub fn insert(&mut self, name: &str, definition: GeneDefinition) {
+        self.definitions.insert(name.to_string(), definition);
+        self.version += 1;
+    }
+    
+    /// Set (or clear, with `None`) a gene's mutation rate override
+    /// Returns false if the gene is not registered
+    pub fn set_mutation_rate(&mut self, name: &str, rate: Option<f32>) -> bool {
+        match self.definitions.get_mut(name) {
+            Some(def) => {
+                def.mutation_rate = rate;
+                true
+            },
+            None => false,
+        }
+    }
+    
+    /// Gene names in sorted order, so seeded iteration is reproducible
     pub fn names(&self) -> Vec<&String> {
-        self.definitions.keys().collect()
+        let mut names: Vec<&String> = self.definitions.keys().collect();
+        names.sort();
+        names
+    }
+    
+    /// Canonical form of a genome for hashing and comparison
+    ///
+    /// Every registered gene is present (missing ones defaulted), unknown
+    /// genes are dropped, continuous values are quantized to
+    /// `CONTINUOUS_PRECISION`, and keys iterate in sorted order.
+    /// Semantically equal genomes canonicalize identically.
+    pub fn canonicalize(&self, genetics: &HashMap<String, GeneValue>) -> BTreeMap<String, GeneValue> {
+        self.names()
+            .into_iter()
+            .map(|name| {
+                // Defaults are quantized too, so an omitted gene matches an explicit default
+                let value = match genetics.get(name).unwrap_or(&self.definitions[name].default) {
+                    GeneValue::Continuous(f) => GeneValue::Continuous(quantize(*f)),
+                    value => value.clone(),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+    
+    /// Stable 64-bit hash of a genome for deduplication
+    ///
+    /// FNV-1a over the canonical form of the sanitized genome: genes in
+    /// sorted order with missing ones defaulted and unknown ones dropped,
+    /// rgb as its three raw bytes, discrete values as text and continuous
+    /// values as a count of `CONTINUOUS_PRECISION` (1e-4) steps, so values
+    /// closer than that hash alike. Genomes equal after sanitizing share a
+    /// fingerprint.
+    pub fn fingerprint(&self, genetics: &HashMap<String, GeneValue>) -> u64 {
+        let sanitized: HashMap<String, GeneValue> = genetics.iter()
+            .filter_map(|(name, value)| {
+                self.definitions.get(name).map(|def| (name.clone(), def.sanitize(value.clone())))
+            })
+            .collect();
+        
+        let mut hash = Fnv::new();
+        for (name, value) in self.canonicalize(&sanitized) {
+            hash.feed(name.as_bytes());
+            match value {
+                GeneValue::Rgb(c) => hash.feed(&[0, 0, c.r, c.g, c.b]),
+                GeneValue::Continuous(f) => {
+                    hash.feed(&[0, 1]);
+                    hash.feed(&((f / CONTINUOUS_PRECISION).round() as i64).to_le_bytes());
+                },
+                GeneValue::Discrete(text) => {
+                    hash.feed(&[0, 2]);
+                    hash.feed(text.as_bytes());
+                    hash.feed(&[0]);
+                },
+            }
+        }
+        hash.0
+    }
+    
+    /// Stable 64-bit hash of the registry's gene names and types
+    ///
+    /// Unlike `version`, two independently built registries with the same
+    /// gene set hash alike, so saves can record which gene set they used.
+    /// Defaults, ranges and options do not contribute.
+    pub fn registry_hash(&self) -> u64 {
+        let mut hash = Fnv::new();
+        for name in self.names() {
+            hash.feed(name.as_bytes());
+            hash.feed(&[0]);
+            hash.feed(self.definitions[name].gene_type.as_bytes());
+            hash.feed(&[0]);
+        }
+        hash.0
+    }
+    
+    /// Registered genes missing from `keys`, and keys the registry doesn't
+    /// know, both sorted
+    pub fn key_drift<'a>(&self, keys: impl IntoIterator<Item = &'a String>) -> (Vec<String>, Vec<String>) {
+        let keys: std::collections::BTreeSet<&String> = keys.into_iter().collect();
+        let missing = self.names()
+            .into_iter()
+            .filter(|name| !keys.contains(name))
+            .cloned()
+            .collect();
+        let unknown = keys.into_iter()
+            .filter(|key| !self.definitions.contains_key(*key))
+            .cloned()
+            .collect();
+        (missing, unknown)
     }
     
     pub fn get_defaults(&self) -> HashMap<String, GeneValue> {
@@ -123,23 +506,24 @@ impl GeneDefinitions {
     }
     
     pub fn generate_random(&self) -> HashMap<String, GeneValue> {
-        let mut rng = rand::thread_rng();
+        self.generate_random_with(&mut rand::thread_rng())
+    }
+    
+    /// Generate random genetics drawing from the given RNG
+    pub fn generate_random_with<R: Rng + ?Sized>(&self, rng: &mut R) -> HashMap<String, GeneValue> {
         let mut genetics = HashMap::new();
         
-        for (name, def) in &self.definitions {
+        for name in self.names() {
+            let def = &self.definitions[name];
             let value = match def.gene_type.as_str() {
                 "rgb" => GeneValue::Rgb(Rgb::new(
                     rng.gen_range(0..=255),
                     rng.gen_range(0..=255),
                     rng.gen_range(0..=255),
                 )),
-                "discrete" => {
-                    if let Some(options) = &def.discrete_options {
-                        let idx = rng.gen_range(0..options.len());
-                        GeneValue::Discrete(options[idx].clone())
-                    } else {
-                        def.default.clone()
-                    }
+                "discrete" => match def.pick_option(rng, None) {
+                    Some(option) => GeneValue::Discrete(option),
+                    None => def.default.clone(),
                 },
                 "continuous" => {
                     if let Some((min, max)) = def.continuous_range {
@@ -155,4 +539,117 @@ impl GeneDefinitions {
         
         genetics
     }
+    
+    /// Regenerate genome `index` of a traced batch seeded with `seed`
+    /// Each genome draws from its own stream `derive_seed(seed, index)`
+    pub fn generate_from_trace(&self, seed: u64, index: usize) -> HashMap<String, GeneValue> {
+        self.generate_random_with(&mut rng::seeded(rng::derive_seed(seed, index as u64)))
+    }
+    
+    /// Generate random genetics whose colors cluster around a theme hue
+    ///
+    /// Every rgb gene gets a hue within `spread` degrees of `theme_hue`
+    /// with random (but clearly non-gray) saturation and mid-range
+    /// lightness; non-color genes are fully random.
+    pub fn generate_themed_with<R: Rng + ?Sized>(&self, rng: &mut R, theme_hue: f32, spread: f32) -> HashMap<String, GeneValue> {
+        let mut genetics = self.generate_random_with(rng);
+        let spread = spread.abs();
+        
+        for name in self.names() {
+            if self.definitions[name].gene_type != "rgb" {
+                continue;
+            }
+            let hue = theme_hue + rng.gen_range(-spread..=spread);
+            let saturation = rng.gen_range(0.4..=1.0);
+            let lightness = rng.gen_range(0.25..=0.75);
+            genetics.insert(name.clone(), GeneValue::Rgb(Rgb::from_hsl(hue, saturation, lightness)));
+        }
+        
+        genetics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Shortest angle between two hues, in degrees
+    fn hue_gap(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+    
+    #[test]
+    fn themed_colors_stay_in_the_hue_band() {
+        let definitions = GeneDefinitions::new();
+        let mut rng = rng::seeded(11);
+        for (theme, spread) in [(200.0, 20.0), (5.0, 15.0)] {
+            for _ in 0..50 {
+                let genetics = definitions.generate_themed_with(&mut rng, theme, spread);
+                let (hue, _, _) = genetics["shell_base_color"].as_rgb().unwrap().to_hsl();
+                // Rounding to 8-bit channels can nudge the hue slightly
+                assert!(hue_gap(hue, theme) <= spread + 3.0, "{} outside {} +/- {}", hue, theme, spread);
+            }
+        }
+    }
+    
+    #[test]
+    fn differently_ordered_partial_genomes_canonicalize_equal() {
+        let definitions = GeneDefinitions::new();
+        let mut a = HashMap::new();
+        a.insert("leg_length".to_string(), GeneValue::Continuous(1.200001));
+        a.insert("limb_shape".to_string(), GeneValue::Discrete("fins".to_string()));
+        let mut b = HashMap::new();
+        b.insert("limb_shape".to_string(), GeneValue::Discrete("fins".to_string()));
+        b.insert("leg_length".to_string(), GeneValue::Continuous(1.2));
+        // A missing gene canonicalizes to its default
+        b.insert("eye_size_modifier".to_string(), GeneValue::Continuous(1.0));
+        
+        let canonical = definitions.canonicalize(&a);
+        assert_eq!(canonical, definitions.canonicalize(&b));
+        assert_eq!(canonical.len(), definitions.names().len());
+        let again: HashMap<_, _> = canonical.clone().into_iter().collect();
+        assert_eq!(definitions.canonicalize(&again), canonical);
+    }
+    
+    /// A schema overlapping the default registry on `leg_length` and
+    /// adding `tail_length`
+    fn modded_schema() -> String {
+        let mut other = GeneDefinitions::new();
+        other.insert("leg_length", GeneDefinition::continuous((0.2, 2.0), 1.0, "Modded legs"));
+        other.insert("tail_length", GeneDefinition::continuous((0.5, 1.5), 1.0, "Tail length"));
+        other.to_schema_json().unwrap()
+    }
+    
+    #[test]
+    fn merge_registry_under_each_policy() {
+        let other = GeneDefinitions::from_schema_json(&modded_schema()).unwrap();
+        let original = GeneDefinitions::new().get("leg_length").unwrap().clone();
+        
+        let mut keep = GeneDefinitions::new();
+        keep.merge(&other, ConflictPolicy::Keep).unwrap();
+        assert_eq!(keep.get("leg_length"), Some(&original));
+        assert!(keep.get("tail_length").is_some());
+        assert!(keep.version() > other.version());
+        
+        let mut overwrite = GeneDefinitions::new();
+        overwrite.merge(&other, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(overwrite.get("leg_length").unwrap().continuous_range, Some((0.2, 2.0)));
+        assert!(overwrite.get("tail_length").is_some());
+        
+        let mut error = GeneDefinitions::new();
+        assert_eq!(error.merge(&other, ConflictPolicy::Error), Err("leg_length".to_string()));
+        assert!(error.get("tail_length").is_none());
+    }
+    
+    #[test]
+    fn trace_entries_regenerate_their_genomes() {
+        let definitions = GeneDefinitions::new();
+        let batch: Vec<_> = (0..8).map(|index| definitions.generate_from_trace(99, index)).collect();
+        
+        let regenerated = GeneDefinitions::new().generate_from_trace(99, 5);
+        assert_eq!(regenerated, batch[5]);
+        assert_eq!(regenerated, definitions.generate_random_with(&mut rng::seeded(rng::derive_seed(99, 5))));
+        assert_ne!(batch[4], batch[5]);
+    }
 }
@@ -2,9 +2,29 @@
 
 use std::collections::HashMap;
 use rand::Rng;
-use crate::types::{GeneValue, Rgb};
+use crate::types::GeneValue;
 use super::genes::GeneDefinitions;
 
+/// Color space used when blending parent colors
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BlendMode {
+    /// Linear per-channel RGB interpolation
+    #[default]
+    Rgb,
+    /// Shortest-arc hue interpolation; avoids muddy midpoints
+    Hsl,
+}
+
+impl BlendMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "rgb" => Some(BlendMode::Rgb),
+            "hsl" => Some(BlendMode::Hsl),
+            _ => None,
+        }
+    }
+}
+
 /// Implements Mendelian inheritance patterns
 pub struct Inheritance {
     definitions: GeneDefinitions,
@@ -16,21 +36,64 @@ impl Inheritance {
     }
     
     /// Basic Mendelian inheritance (50/50 chance from each parent)
+    /// Discrete genes with a dominance order pass on the dominant allele;
+    /// linked genes share one parent-of-origin flip per group
     pub fn inherit(
         &self,
         parent1: &HashMap<String, GeneValue>,
         parent2: &HashMap<String, GeneValue>,
     ) -> HashMap<String, GeneValue> {
-        let mut rng = rand::thread_rng();
+        self.inherit_with(&mut rand::thread_rng(), parent1, parent2)
+    }
+    
+    /// Mendelian inheritance drawing from the given RNG
+    pub fn inherit_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+    ) -> HashMap<String, GeneValue> {
+        self.inherit_weighted_with(rng, parent1, parent2, 0.5)
+    }
+    
+    /// Inheritance where each gene comes from parent 1 with probability
+    /// `p1_weight` (clamped to [0, 1]) instead of 50/50
+    pub fn inherit_weighted(
+        &self,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        p1_weight: f32,
+    ) -> HashMap<String, GeneValue> {
+        self.inherit_weighted_with(&mut rand::thread_rng(), parent1, parent2, p1_weight)
+    }
+    
+    /// Weighted inheritance drawing from the given RNG
+    pub fn inherit_weighted_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        p1_weight: f32,
+    ) -> HashMap<String, GeneValue> {
+        let p1_chance = p1_weight.clamp(0.0, 1.0) as f64;
         let mut child = HashMap::new();
+        // One flip per linkage group, made the first time the group needs it
+        let mut group_flips: Vec<Option<bool>> = vec![None; self.definitions.linkage_count()];
         
         for name in self.definitions.names() {
             let p1_value = parent1.get(name);
             let p2_value = parent2.get(name);
             
             let value = match (p1_value, p2_value) {
-                (Some(v1), Some(v2)) => {
-                    if rng.gen_bool(0.5) { v1.clone() } else { v2.clone() }
+                (Some(v1), Some(v2)) => match self.dominant(name, v1, v2) {
+                    Some(v) => v.clone(),
+                    None => {
+                        let from_first = match self.definitions.linkage_group(name) {
+                            Some(group) => *group_flips[group].get_or_insert_with(|| rng.gen_bool(p1_chance)),
+                            None => rng.gen_bool(p1_chance),
+                        };
+                        if from_first { v1.clone() } else { v2.clone() }
+                    },
                 },
                 (Some(v), None) | (None, Some(v)) => v.clone(),
                 (None, None) => {
@@ -48,13 +111,152 @@ impl Inheritance {
         child
     }
     
+    /// Mendelian inheritance with some genes pinned to one parent
+    pub fn inherit_masked(
+        &self,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        mask: &HashMap<String, u8>,
+    ) -> HashMap<String, GeneValue> {
+        self.inherit_masked_with(&mut rand::thread_rng(), parent1, parent2, mask)
+    }
+    
+    /// Masked inheritance drawing from the given RNG
+    ///
+    /// A mask value of 0 takes the gene from parent 1 and any other value
+    /// from parent 2, overriding dominance and linkage; if that parent
+    /// lacks the gene it is inherited normally. Unmasked genes follow
+    /// `inherit_with`, and mask entries for unregistered genes are ignored.
+    pub fn inherit_masked_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        mask: &HashMap<String, u8>,
+    ) -> HashMap<String, GeneValue> {
+        let mut child = self.inherit_with(rng, parent1, parent2);
+        for (name, &source) in mask {
+            if self.definitions.get(name).is_none() {
+                continue;
+            }
+            let parent = if source == 0 { parent1 } else { parent2 };
+            if let Some(value) = parent.get(name) {
+                child.insert(name.clone(), value.clone());
+            }
+        }
+        child
+    }
+    
+    /// Mendelian inheritance re-rolled until the child is viable
+    ///
+    /// Tries up to `max_attempts` times (at least once) and returns the
+    /// first child that avoids every lethal combo, or the last attempt if
+    /// none did.
+    pub fn inherit_viable_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        max_attempts: usize,
+    ) -> HashMap<String, GeneValue> {
+        let mut child = self.inherit_with(rng, parent1, parent2);
+        for _ in 1..max_attempts {
+            if self.definitions.is_viable(&child) {
+                break;
+            }
+            child = self.inherit_with(rng, parent1, parent2);
+        }
+        child
+    }
+    
+    /// Chromosome-style crossover over the genes in sorted name order
+    ///
+    /// Picks `points` distinct cut positions; genes before the first cut
+    /// come from parent 1, then the source parent alternates at each cut.
+    /// Neighbouring genes therefore tend to travel together. `points` is
+    /// capped at one less than the gene count; dominance and linkage
+    /// groups do not apply.
+    pub fn inherit_crossover(
+        &self,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        points: usize,
+    ) -> HashMap<String, GeneValue> {
+        self.inherit_crossover_with(&mut rand::thread_rng(), parent1, parent2, points)
+    }
+    
+    /// Crossover inheritance drawing cut positions from the given RNG
+    pub fn inherit_crossover_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        points: usize,
+    ) -> HashMap<String, GeneValue> {
+        let names = self.definitions.names();
+        let cut_count = points.min(names.len().saturating_sub(1));
+        let mut cuts = rand::seq::index::sample(rng, names.len().saturating_sub(1), cut_count)
+            .into_iter()
+            .map(|i| i + 1)
+            .collect::<Vec<_>>();
+        cuts.sort_unstable();
+        
+        let mut child = HashMap::new();
+        let mut from_first = true;
+        let mut next_cut = cuts.iter().peekable();
+        for (i, name) in names.into_iter().enumerate() {
+            if next_cut.next_if(|&&cut| cut == i).is_some() {
+                from_first = !from_first;
+            }
+            let (primary, secondary) = if from_first { (parent1, parent2) } else { (parent2, parent1) };
+            let value = match primary.get(name).or_else(|| secondary.get(name)) {
+                Some(v) => v.clone(),
+                None => match self.definitions.get(name) {
+                    Some(def) => def.default.clone(),
+                    None => continue,
+                },
+            };
+            child.insert(name.clone(), value);
+        }
+        
+        child
+    }
+    
+    /// The dominant of two differing discrete alleles, if the gene ranks them
+    fn dominant<'a>(&self, name: &str, v1: &'a GeneValue, v2: &'a GeneValue) -> Option<&'a GeneValue> {
+        let def = self.definitions.get(name)?;
+        let r1 = def.dominance_rank(v1.as_discrete()?)?;
+        let r2 = def.dominance_rank(v2.as_discrete()?)?;
+        match r1.cmp(&r2) {
+            std::cmp::Ordering::Less => Some(v1),
+            std::cmp::Ordering::Greater => Some(v2),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+    
     /// Blended inheritance (average continuous values, mix colors)
     pub fn inherit_blended(
         &self,
         parent1: &HashMap<String, GeneValue>,
         parent2: &HashMap<String, GeneValue>,
     ) -> HashMap<String, GeneValue> {
-        let mut rng = rand::thread_rng();
+        self.inherit_blended_with(&mut rand::thread_rng(), parent1, parent2, BlendMode::default(), 0.5)
+    }
+    
+    /// Blended inheritance drawing from the given RNG, mixing colors in `mode`
+    ///
+    /// `p1_weight` (clamped to [0, 1]) is parent 1's share: continuous genes
+    /// take the weighted average, color blends shift toward parent 1 and
+    /// discrete genes come from parent 1 with that probability.
+    pub fn inherit_blended_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        parent1: &HashMap<String, GeneValue>,
+        parent2: &HashMap<String, GeneValue>,
+        mode: BlendMode,
+        p1_weight: f32,
+    ) -> HashMap<String, GeneValue> {
+        let w = p1_weight.clamp(0.0, 1.0);
         let mut child = HashMap::new();
         
         for name in self.definitions.names() {
@@ -69,16 +271,20 @@ impl Inheritance {
             let value = match (p1_value, p2_value, def.gene_type.as_str()) {
                 // Blend RGB colors
                 (Some(GeneValue::Rgb(c1)), Some(GeneValue::Rgb(c2)), "rgb") => {
-                    let bias = rng.gen_range(0.3..0.7);
-                    GeneValue::Rgb(c1.blend(c2, bias))
+                    // Bias is parent 2's share: jittered ±0.2 around 1 - w
+                    let bias = (rng.gen_range(0.3..0.7) + 0.5 - w).clamp(0.0, 1.0);
+                    GeneValue::Rgb(match mode {
+                        BlendMode::Rgb => c1.blend(c2, bias),
+                        BlendMode::Hsl => c1.blend_hsl(c2, bias),
+                    })
                 },
-                // Average continuous values
+                // Weighted average of continuous values
                 (Some(GeneValue::Continuous(f1)), Some(GeneValue::Continuous(f2)), "continuous") => {
-                    GeneValue::Continuous((f1 + f2) / 2.0)
+                    GeneValue::Continuous(f1 * w + f2 * (1.0 - w))
                 },
                 // Discrete: random from parent
                 (Some(v1), Some(v2), "discrete") => {
-                    if rng.gen_bool(0.5) { v1.clone() } else { v2.clone() }
+                    if rng.gen_bool(w as f64) { v1.clone() } else { v2.clone() }
                 },
                 // Fallback
                 (Some(v), None, _) | (None, Some(v), _) => v.clone(),
@@ -111,29 +317,47 @@ impl Inheritance {
             
             total += 1.0;
             
-            match (v1, v2, def.gene_type.as_str()) {
-                (Some(GeneValue::Rgb(c1)), Some(GeneValue::Rgb(c2)), "rgb") => {
-                    // Color similarity based on Euclidean distance
-                    let max_dist = (255.0_f32.powi(2) * 3.0).sqrt();
-                    let dist = c1.distance(c2);
-                    similar += 1.0 - (dist / max_dist);
-                },
-                (Some(GeneValue::Continuous(f1)), Some(GeneValue::Continuous(f2)), "continuous") => {
-                    if let Some((min, max)) = def.continuous_range {
-                        let range = max - min;
-                        let diff = (f1 - f2).abs() / range;
-                        similar += 1.0 - diff;
-                    }
-                },
-                (Some(GeneValue::Discrete(s1)), Some(GeneValue::Discrete(s2)), "discrete") => {
-                    if s1 == s2 {
-                        similar += 1.0;
-                    }
-                },
-                _ => {},
+            if let (Some(v1), Some(v2)) = (v1, v2) {
+                if let Some(distance) = v1.normalized_distance(v2, def) {
+                    similar += 1.0 - distance;
+                }
             }
         }
         
         if total > 0.0 { similar / total } else { 0.0 }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    
+    #[test]
+    fn linked_genes_come_from_the_same_parent() {
+        let mut definitions = GeneDefinitions::new();
+        definitions.link(vec!["leg_length".to_string(), "leg_thickness_modifier".to_string()]).unwrap();
+        let inheritance = Inheritance::new(definitions.clone());
+        
+        let mut p1 = definitions.get_defaults();
+        p1.insert("leg_length".to_string(), GeneValue::Continuous(0.6));
+        p1.insert("leg_thickness_modifier".to_string(), GeneValue::Continuous(0.8));
+        let mut p2 = definitions.get_defaults();
+        p2.insert("leg_length".to_string(), GeneValue::Continuous(1.4));
+        p2.insert("leg_thickness_modifier".to_string(), GeneValue::Continuous(1.2));
+        
+        let mut rng = rng::seeded(12);
+        let mut from_p1 = 0;
+        for _ in 0..500 {
+            let child = inheritance.inherit_with(&mut rng, &p1, &p2);
+            let legs = (&child["leg_length"], &child["leg_thickness_modifier"]);
+            if legs == (&p1["leg_length"], &p1["leg_thickness_modifier"]) {
+                from_p1 += 1;
+            } else {
+                assert_eq!(legs, (&p2["leg_length"], &p2["leg_thickness_modifier"]));
+            }
+        }
+        // The shared flip still favours neither parent
+        assert!((150..350).contains(&from_p1), "{}", from_p1);
+    }
+}
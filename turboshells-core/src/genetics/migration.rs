@@ -0,0 +1,106 @@
+//! Genome migrations - upgrade old saves across registry versions
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Rules that upgrade a genome from one registry version to the next
+///
+/// Applied in order: renames, then default fills, then removals.
+#[derive(Clone, Debug, Default)]
+pub struct MigrationRules {
+    /// (old name, new name) pairs; the value carries over to the new name
+    pub renames: Vec<(String, String)>,
+    /// Genes filled with their registry default when missing
+    pub fill_defaults: Vec<String>,
+    /// Genes dropped from the genome
+    pub removals: Vec<String>,
+}
+
+/// Registered migrations keyed by the version they upgrade from
+#[derive(Clone, Debug, Default)]
+pub struct Migrations {
+    rules: BTreeMap<u32, MigrationRules>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Register the rules upgrading `from_version` to `from_version + 1`
+    /// Re-registering a version replaces its rules
+    pub fn register(&mut self, from_version: u32, rules: MigrationRules) {
+        self.rules.insert(from_version, rules);
+    }
+    
+    /// Upgrade a genome saved at `from_version` up to `to_version`
+    ///
+    /// Works on any value type so raw (not yet validated) genomes can be
+    /// migrated before their old gene names are dropped as unknown.
+    /// `default_for` supplies values for default-fill rules.
+    pub fn apply<V>(
+        &self,
+        genome: &mut HashMap<String, V>,
+        from_version: u32,
+        to_version: u32,
+        default_for: impl Fn(&str) -> Option<V>,
+    ) {
+        for (_, rules) in self.rules.range(from_version..to_version) {
+            for (old, new) in &rules.renames {
+                if let Some(value) = genome.remove(old) {
+                    genome.insert(new.clone(), value);
+                }
+            }
+            for name in &rules.fill_defaults {
+                if !genome.contains_key(name) {
+                    if let Some(value) = default_for(name) {
+                        genome.insert(name.clone(), value);
+                    }
+                }
+            }
+            for name in &rules.removals {
+                genome.remove(name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn rename_carries_value_across_two_versions() {
+        let mut migrations = Migrations::new();
+        migrations.register(1, MigrationRules {
+            renames: vec![("leg_len".to_string(), "leg_length".to_string())],
+            ..MigrationRules::default()
+        });
+        migrations.register(2, MigrationRules {
+            renames: vec![("leg_length".to_string(), "limb_length".to_string())],
+            fill_defaults: vec!["eye_size".to_string()],
+            removals: vec!["obsolete".to_string()],
+        });
+        
+        let mut genome = HashMap::from([
+            ("leg_len".to_string(), 1.25),
+            ("obsolete".to_string(), 0.0),
+        ]);
+        migrations.apply(&mut genome, 1, 3, |_| Some(1.0));
+        assert_eq!(genome, HashMap::from([
+            ("limb_length".to_string(), 1.25),
+            ("eye_size".to_string(), 1.0),
+        ]));
+    }
+    
+    #[test]
+    fn only_migrations_from_the_saved_version_apply() {
+        let mut migrations = Migrations::new();
+        migrations.register(1, MigrationRules {
+            renames: vec![("a".to_string(), "b".to_string())],
+            ..MigrationRules::default()
+        });
+        let mut genome = HashMap::from([("a".to_string(), 2.0)]);
+        migrations.apply(&mut genome, 2, 3, |_| None);
+        assert_eq!(genome, HashMap::from([("a".to_string(), 2.0)]));
+    }
+}
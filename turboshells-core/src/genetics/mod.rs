@@ -2,18 +2,46 @@
 //! 
 //! Provides gene definitions, inheritance, and mutation systems.
 
+mod binary;
+mod diversity;
+mod evolution;
 mod genes;
 mod inheritance;
+mod migration;
 mod mutation;
+mod naming;
+mod pedigree;
+mod phenotype;
+mod presets;
+mod stats;
+mod tween;
+mod validation;
 
-pub use genes::{GeneDefinition, GeneDefinitions};
-pub use inheritance::Inheritance;
-pub use mutation::Mutation;
+pub use binary::{from_bytes, to_bytes};
+pub use diversity::{gene_spread, heritability, population_diversity, similarity_to_all};
+pub use evolution::evolve_with;
+pub use genes::{ConflictPolicy, GeneDefinition, GeneDefinitions, CONTINUOUS_PRECISION, DEFAULT_MUTATION_STRENGTH};
+pub use inheritance::{BlendMode, Inheritance};
+pub use migration::{MigrationRules, Migrations};
+pub use mutation::{anneal_rate, step_size, validate_rate, GeneChange, Mutation, DEFAULT_MACRO_CHANCE, DEFAULT_MUTATION_RATE};
+pub use naming::suggest_name;
+pub use pedigree::Pedigree;
+pub use phenotype::phenotype;
+pub use presets::{preset, preset_names, Presets};
+pub use stats::{derive_stats, genome_for_stats, stat_targets};
+pub use tween::Tween;
+pub use validation::{validate, validate_batch, RawGene, ValidationReport};
 
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use std::collections::HashMap;
-use crate::types::{GeneValue, Rgb};
+use pyo3::IntoPyObjectExt;
+use pyo3::exceptions::{PyKeyError, PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PyDict};
+use rand::RngCore;
+use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use crate::rng;
+use crate::types::{CvdMode, GeneValue, Rgb};
 
 /// Python-exposed Genetics class
 #[pyclass]
@@ -21,66 +49,1074 @@ pub struct PyGenetics {
     definitions: GeneDefinitions,
     inheritance: Inheritance,
     mutation: Mutation,
+    /// Built-in breeds, validated against the current registry
+    presets: Presets,
+    migrations: Migrations,
+    /// Shared stream for breeding, mutation and generation; behind a lock
+    /// so drawing from it doesn't need `&mut self`
+    rng: Mutex<rng::SeededRng>,
+    /// Clamp continuous genes and reset unknown discrete options when
+    /// reading dicts from Python
+    #[pyo3(get, set)]
+    clamp_on_import: bool,
 }
 
 #[pymethods]
 impl PyGenetics {
+    /// Create a genetics engine; pass `seed` for reproducible results
     #[new]
-    pub fn new() -> Self {
+    #[pyo3(signature = (seed=None))]
+    pub fn new(seed: Option<u64>) -> Self {
         let definitions = GeneDefinitions::new();
         Self {
             inheritance: Inheritance::new(definitions.clone()),
             mutation: Mutation::new(definitions.clone()),
+            presets: Presets::new(&definitions),
+            migrations: Migrations::new(),
+            rng: Mutex::new(rng::seeded(seed.unwrap_or_else(rng::random_seed))),
+            clamp_on_import: false,
             definitions,
         }
     }
     
+    /// Restart the shared RNG from a seed
+    pub fn reseed(&self, seed: u64) {
+        *self.rng() = rng::seeded(seed);
+    }
+    
+    /// Create a genetics engine from an exported schema
+    #[staticmethod]
+    pub fn from_schema(schema_json: &str) -> PyResult<Self> {
+        let definitions = GeneDefinitions::from_schema_json(schema_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid schema: {}", e)))?;
+        let mut genetics = Self::new(None);
+        genetics.definitions = definitions;
+        genetics.rebuild();
+        Ok(genetics)
+    }
+    
+    /// Export the gene registry as a JSON schema
+    pub fn export_schema(&self) -> PyResult<String> {
+        self.definitions.to_schema_json()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+    
+    /// Merge genes from another exported schema into this registry
+    /// `on_conflict` is "keep", "overwrite" or "error"; under "error" a
+    /// differently-defined shared gene raises and nothing is merged
+    pub fn merge_registry(&mut self, other_schema_json: &str, on_conflict: &str) -> PyResult<()> {
+        let policy = ConflictPolicy::parse(on_conflict)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown conflict policy '{}'", on_conflict)))?;
+        let other = GeneDefinitions::from_schema_json(other_schema_json)
+            .map_err(|e| PyValueError::new_err(format!("Invalid schema: {}", e)))?;
+        
+        self.definitions.merge(&other, policy)
+            .map_err(|name| PyValueError::new_err(format!("Conflicting definition for gene '{}'", name)))?;
+        self.rebuild();
+        Ok(())
+    }
+    
+    /// Describe every registered gene for building editors
+    ///
+    /// Maps each gene name to a dict with `type`, `description` and
+    /// `default` (native Python form), plus `options` for discrete genes
+    /// and a `(min, max)` `range` for continuous ones. Discrete genes with
+    /// a dominance order also list it under `dominance`, and weighted ones
+    /// their option `weights`.
+    pub fn describe_genes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for name in self.definitions.names() {
+            let def = self.definitions.get(name).unwrap();
+            let entry = PyDict::new(py);
+            entry.set_item("type", &def.gene_type)?;
+            entry.set_item("description", &def.description)?;
+            entry.set_item("default", gene_to_py(py, &def.default)?)?;
+            if let Some(options) = &def.discrete_options {
+                entry.set_item("options", options)?;
+            }
+            if let Some(range) = def.continuous_range {
+                entry.set_item("range", range)?;
+                entry.set_item("mutation_strength", def.continuous_mutation_strength)?;
+            }
+            if let Some(dominance) = &def.dominance {
+                entry.set_item("dominance", dominance)?;
+            }
+            if let Some(weights) = &def.discrete_weights {
+                entry.set_item("weights", weights)?;
+            }
+            dict.set_item(name, entry)?;
+        }
+        Ok(dict)
+    }
+    
+    /// Register a custom gene at runtime
+    ///
+    /// `gene_type` is "rgb" (default an `(r, g, b)` tuple), "discrete"
+    /// (non-empty `options` containing the default) or "continuous"
+    /// (`range` with min < max containing the default). Discrete genes may
+    /// give a `dominance` order (most dominant first) drawn from `options`.
+    /// Continuous genes may set `mutation_strength`, the mutation std dev as
+    /// a fraction of the range (0.1 by default). Discrete genes may give
+    /// `weights`, one non-negative relative odds per option, used when
+    /// generating and mutating (uniform if omitted).
+    /// Re-registering an existing name overwrites it. Bumps the registry version.
+    #[pyo3(signature = (name, gene_type, default, options=None, range=None, description="", dominance=None, mutation_strength=None, weights=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_gene(
+        &mut self,
+        name: &str,
+        gene_type: &str,
+        default: &Bound<'_, PyAny>,
+        options: Option<Vec<String>>,
+        range: Option<(f32, f32)>,
+        description: &str,
+        dominance: Option<Vec<String>>,
+        mutation_strength: Option<f32>,
+        weights: Option<Vec<f32>>,
+    ) -> PyResult<()> {
+        if dominance.is_some() && gene_type != "discrete" {
+            return Err(PyValueError::new_err("dominance only applies to discrete genes"));
+        }
+        if weights.is_some() && gene_type != "discrete" {
+            return Err(PyValueError::new_err("weights only apply to discrete genes"));
+        }
+        if mutation_strength.is_some() && gene_type != "continuous" {
+            return Err(PyValueError::new_err("mutation_strength only applies to continuous genes"));
+        }
+        if mutation_strength.is_some_and(|s| !(0.0..=1.0).contains(&s)) {
+            return Err(PyValueError::new_err("mutation_strength must be between 0 and 1"));
+        }
+        let mut definition = match gene_type {
+            "rgb" => {
+                if options.is_some() || range.is_some() {
+                    return Err(PyValueError::new_err("rgb genes take neither options nor range"));
+                }
+                let color: (u8, u8, u8) = default.extract()
+                    .map_err(|_| PyValueError::new_err("rgb default must be an (r, g, b) tuple"))?;
+                GeneDefinition::rgb(Rgb::from_tuple(color), description)
+            },
+            "discrete" => {
+                if range.is_some() {
+                    return Err(PyValueError::new_err("discrete genes take options, not range"));
+                }
+                let options = options.filter(|o| !o.is_empty())
+                    .ok_or_else(|| PyValueError::new_err("discrete genes need a non-empty options list"))?;
+                let default: String = default.extract()
+                    .map_err(|_| PyValueError::new_err("discrete default must be a string"))?;
+                if !options.contains(&default) {
+                    return Err(PyValueError::new_err(format!("Default '{}' is not one of the options", default)));
+                }
+                GeneDefinition::discrete(options.iter().map(String::as_str).collect(), &default, description)
+            },
+            "continuous" => {
+                if options.is_some() {
+                    return Err(PyValueError::new_err("continuous genes take range, not options"));
+                }
+                let (min, max) = range.filter(|(min, max)| min < max)
+                    .ok_or_else(|| PyValueError::new_err("continuous genes need a range with min < max"))?;
+                let default: f32 = default.extract()
+                    .map_err(|_| PyValueError::new_err("continuous default must be a number"))?;
+                if !(min..=max).contains(&default) {
+                    return Err(PyValueError::new_err(format!("Default {} is outside range ({}, {})", default, min, max)));
+                }
+                GeneDefinition::continuous((min, max), default, description)
+            },
+            _ => return Err(PyValueError::new_err(format!("Unknown gene type '{}'", gene_type))),
+        };
+        
+        if let Some(order) = &dominance {
+            let options = definition.discrete_options.as_deref().unwrap_or_default();
+            if let Some(allele) = order.iter().find(|a| !options.contains(a)) {
+                return Err(PyValueError::new_err(format!("Dominance allele '{}' is not one of the options", allele)));
+            }
+        }
+        definition.dominance = dominance;
+        if let Some(weights) = &weights {
+            let option_count = definition.discrete_options.as_ref().map_or(0, Vec::len);
+            if weights.len() != option_count {
+                return Err(PyValueError::new_err(format!("Got {} weights for {} options", weights.len(), option_count)));
+            }
+            if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+                return Err(PyValueError::new_err("weights must be non-negative numbers"));
+            }
+        }
+        definition.discrete_weights = weights;
+        if let Some(strength) = mutation_strength {
+            definition.continuous_mutation_strength = strength;
+        }
+        
+        self.definitions.insert(name, definition);
+        self.rebuild();
+        Ok(())
+    }
+    
+    /// Link genes so `inherit` takes them all from the same parent
+    /// A gene belongs to at most one group; linking it again moves it
+    pub fn link_genes(&mut self, group: Vec<String>) -> PyResult<()> {
+        self.definitions.link(group)
+            .map_err(|name| PyKeyError::new_err(format!("Unknown gene '{}'", name)))?;
+        self.rebuild();
+        Ok(())
+    }
+    
+    /// Mark a combination of discrete gene values as lethal
+    ///
+    /// `genes` maps gene names to values; a genome carrying all of them is
+    /// not viable. Only discrete genes participate: rgb or continuous
+    /// genes, unknown genes, values outside the options and an empty dict
+    /// raise ValueError.
+    pub fn register_lethal_combo(&mut self, genes: HashMap<String, String>) -> PyResult<()> {
+        self.definitions.add_lethal_combo(genes.into_iter().collect())
+            .map_err(PyValueError::new_err)?;
+        self.rebuild();
+        Ok(())
+    }
+    
+    /// False if the genome carries every value of any lethal combo
+    /// Missing genes count as their default
+    pub fn is_viable(&self, genetics: &Bound<'_, PyDict>) -> PyResult<bool> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        Ok(self.definitions.is_viable(&genes))
+    }
+    
+    /// Current gene registry version
+    #[getter]
+    pub fn registry_version(&self) -> u32 {
+        self.definitions.version()
+    }
+    
+    /// Hex hash of the registry's gene names and types
+    /// Equal for registries with the same gene set, however they were built
+    #[getter]
+    pub fn registry_hash(&self) -> String {
+        format!("{:016x}", self.definitions.registry_hash())
+    }
+    
+    /// Register rules upgrading genomes from `from_version` to the next version
+    ///
+    /// `rules` may contain `rename` (dict old -> new), `fill_defaults`
+    /// (list of genes set to their default when missing) and `remove`
+    /// (list of genes to drop). The registry version is left alone; it
+    /// only moves when the gene definitions themselves change.
+    pub fn register_migration(&mut self, from_version: u32, rules: &Bound<'_, PyDict>) -> PyResult<()> {
+        let mut parsed = MigrationRules::default();
+        for (key, value) in rules.iter() {
+            let key: String = key.extract()?;
+            match key.as_str() {
+                "rename" => {
+                    let renames: HashMap<String, String> = value.extract()?;
+                    let mut renames: Vec<_> = renames.into_iter().collect();
+                    renames.sort();
+                    parsed.renames = renames;
+                },
+                "fill_defaults" => parsed.fill_defaults = value.extract()?,
+                "remove" => parsed.removals = value.extract()?,
+                _ => return Err(PyValueError::new_err(format!("Unknown migration rule '{}'", key))),
+            }
+        }
+        
+        self.migrations.register(from_version, parsed);
+        Ok(())
+    }
+    
+    /// Upgrade a genome saved at `from_version` to the current registry
+    ///
+    /// Applies each registered migration in version order (none when
+    /// `from_version` is omitted), then fills registered genes still
+    /// missing with their defaults and drops genes still unknown.
+    #[pyo3(signature = (genetics, from_version=None))]
+    pub fn migrate<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, from_version: Option<u32>) -> PyResult<Bound<'py, PyDict>> {
+        Ok(self.migrate_with_summary(py, genetics, from_version)?.0)
+    }
+    
+    /// `migrate`, also returning a summary of what changed
+    ///
+    /// The summary has `added` (genes filled with defaults) and `dropped`
+    /// (unknown genes removed), both sorted and compared against the
+    /// genome after any versioned migrations.
+    #[pyo3(signature = (genetics, from_version=None))]
+    pub fn migrate_with_summary<'py>(
+        &self,
+        py: Python<'py>,
+        genetics: &Bound<'py, PyDict>,
+        from_version: Option<u32>,
+    ) -> PyResult<(Bound<'py, PyDict>, Bound<'py, PyDict>)> {
+        let current = self.definitions.version();
+        let from_version = from_version.unwrap_or(current);
+        if from_version > current {
+            return Err(PyValueError::new_err(format!(
+                "Genome version {} is newer than registry version {}", from_version, current
+            )));
+        }
+        
+        let mut raw: HashMap<String, PyObject> = genetics.extract()?;
+        self.migrations.apply(&mut raw, from_version, current, |name| {
+            self.definitions.get(name).and_then(|def| gene_to_py(py, &def.default).ok())
+        });
+        
+        let (added, dropped) = self.definitions.key_drift(raw.keys());
+        let migrated = PyDict::new(py);
+        for (key, value) in raw {
+            migrated.set_item(key, value)?;
+        }
+        let mut genes = self.pydict_to_genetics(&migrated)?;
+        for name in &added {
+            genes.insert(name.clone(), self.definitions.get(name).unwrap().default.clone());
+        }
+        
+        let summary = PyDict::new(py);
+        summary.set_item("added", added)?;
+        summary.set_item("dropped", dropped)?;
+        Ok((self.genetics_to_pydict(py, &genes)?, summary))
+    }
+    
+    /// Whether a genome's genes are exactly the registry's gene set
+    /// False means it was saved with a different set; see `migrate`
+    pub fn genetics_version(&self, genetics: &Bound<'_, PyDict>) -> PyResult<bool> {
+        let keys: Vec<String> = genetics.keys().extract()?;
+        let (missing, unknown) = self.definitions.key_drift(&keys);
+        Ok(missing.is_empty() && unknown.is_empty())
+    }
+    
     /// Generate random genetics
     pub fn generate_random<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
-        let genetics = self.definitions.generate_random();
+        let genetics = self.definitions.generate_random_with(&mut *self.rng());
+        self.genetics_to_pydict(py, &genetics)
+    }
+    
+    /// Generate `count` seeded genomes plus the (seed, index) that made each
+    /// Passing a trace entry to `generate_from_trace` reproduces its genome
+    #[allow(clippy::type_complexity)]
+    pub fn generate_random_traced<'py>(&self, py: Python<'py>, seed: u64, count: usize) -> PyResult<(Vec<Bound<'py, PyDict>>, Vec<(u64, usize)>)> {
+        let genomes = (0..count)
+            .map(|index| self.genetics_to_pydict(py, &self.definitions.generate_from_trace(seed, index)))
+            .collect::<PyResult<_>>()?;
+        let trace = (0..count).map(|index| (seed, index)).collect();
+        Ok((genomes, trace))
+    }
+    
+    /// Rebuild the genome recorded by a `generate_random_traced` trace entry
+    pub fn generate_from_trace<'py>(&self, py: Python<'py>, seed: u64, index: usize) -> PyResult<Bound<'py, PyDict>> {
+        let genetics = self.definitions.generate_from_trace(seed, index);
         self.genetics_to_pydict(py, &genetics)
     }
     
+    /// Generate a seeded cohort whose colors share a theme hue
+    /// Rgb genes fall within `spread` degrees of `theme_hue`; other genes are random
+    pub fn generate_random_themed<'py>(&self, py: Python<'py>, theme_hue: f32, spread: f32, count: usize, seed: u64) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        if !(0.0..=180.0).contains(&spread) {
+            return Err(PyValueError::new_err("spread must be between 0 and 180 degrees"));
+        }
+        let mut rng = rng::seeded(seed);
+        (0..count)
+            .map(|_| {
+                let genetics = self.definitions.generate_themed_with(&mut rng, theme_hue, spread);
+                self.genetics_to_pydict(py, &genetics)
+            })
+            .collect()
+    }
+    
     /// Get default genetics
     pub fn get_defaults<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let genetics = self.definitions.get_defaults();
         self.genetics_to_pydict(py, &genetics)
     }
     
-    /// Inherit genetics from two parents (Mendelian 50/50)
-    pub fn inherit<'py>(&self, py: Python<'py>, parent1: &Bound<'py, PyDict>, parent2: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+    /// Full genetics for a built-in breed ("albino", "galapagos", "painted")
+    pub fn get_preset<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyDict>> {
+        let genetics = self.presets.get(name).map_err(PyValueError::new_err)?;
+        self.genetics_to_pydict(py, &genetics)
+    }
+    
+    /// Names of the built-in breeds, sorted
+    #[staticmethod]
+    pub fn list_presets() -> Vec<&'static str> {
+        preset_names()
+    }
+    
+    /// Inherit genetics from two parents
+    ///
+    /// `mode` "mendelian" (default) picks each gene from `parent1` with
+    /// chance `p1_weight`. "crossover" cuts the genes (sorted by name) at
+    /// `crossover_points` random positions and alternates parents between
+    /// cuts, starting with `parent1`; `p1_weight` is ignored.
+    #[pyo3(signature = (parent1, parent2, p1_weight=0.5, mode="mendelian", crossover_points=1))]
+    pub fn inherit<'py>(
+        &self,
+        py: Python<'py>,
+        parent1: &Bound<'py, PyDict>,
+        parent2: &Bound<'py, PyDict>,
+        p1_weight: f32,
+        mode: &str,
+        crossover_points: usize,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let p1 = self.pydict_to_genetics(parent1)?;
+        let p2 = self.pydict_to_genetics(parent2)?;
+        let child = match mode {
+            "mendelian" => self.inheritance.inherit_weighted_with(&mut *self.rng(), &p1, &p2, p1_weight),
+            "crossover" => self.inheritance.inherit_crossover_with(&mut *self.rng(), &p1, &p2, crossover_points),
+            _ => return Err(PyValueError::new_err(format!("Unknown inheritance mode '{}'", mode))),
+        };
+        self.genetics_to_pydict(py, &child)
+    }
+    
+    /// Inherit with chosen genes forced from one parent
+    ///
+    /// `mask` maps gene names to 0 (take from `parent1`) or 1 (take from
+    /// `parent2`); other genes are inherited as in `inherit`. Unknown gene
+    /// names are ignored; any other mask value raises ValueError.
+    pub fn inherit_masked<'py>(&self, py: Python<'py>, parent1: &Bound<'py, PyDict>, parent2: &Bound<'py, PyDict>, mask: HashMap<String, u8>) -> PyResult<Bound<'py, PyDict>> {
+        if let Some((name, value)) = mask.iter().find(|(_, &v)| v > 1) {
+            return Err(PyValueError::new_err(format!("Mask value for '{}' must be 0 or 1, got {}", name, value)));
+        }
         let p1 = self.pydict_to_genetics(parent1)?;
         let p2 = self.pydict_to_genetics(parent2)?;
-        let child = self.inheritance.inherit(&p1, &p2);
+        let child = self.inheritance.inherit_masked_with(&mut *self.rng(), &p1, &p2, &mask);
+        self.genetics_to_pydict(py, &child)
+    }
+    
+    /// Mendelian inheritance re-rolled until the child is viable
+    /// Returns the last attempt if none of `max_attempts` avoided every
+    /// lethal combo; check it with `is_viable`
+    #[pyo3(signature = (parent1, parent2, max_attempts=10))]
+    pub fn inherit_viable<'py>(&self, py: Python<'py>, parent1: &Bound<'py, PyDict>, parent2: &Bound<'py, PyDict>, max_attempts: usize) -> PyResult<Bound<'py, PyDict>> {
+        let p1 = self.pydict_to_genetics(parent1)?;
+        let p2 = self.pydict_to_genetics(parent2)?;
+        let child = self.inheritance.inherit_viable_with(&mut *self.rng(), &p1, &p2, max_attempts);
         self.genetics_to_pydict(py, &child)
     }
     
     /// Inherit with blending for continuous traits
-    pub fn inherit_blended<'py>(&self, py: Python<'py>, parent1: &Bound<'py, PyDict>, parent2: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+    /// `mode` picks the color space for blending colors: "rgb" or "hsl";
+    /// `p1_weight` is `parent1`'s share of each blend
+    #[pyo3(signature = (parent1, parent2, mode="rgb", p1_weight=0.5))]
+    pub fn inherit_blended<'py>(&self, py: Python<'py>, parent1: &Bound<'py, PyDict>, parent2: &Bound<'py, PyDict>, mode: &str, p1_weight: f32) -> PyResult<Bound<'py, PyDict>> {
+        let mode = BlendMode::parse(mode)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown blend mode '{}'", mode)))?;
         let p1 = self.pydict_to_genetics(parent1)?;
         let p2 = self.pydict_to_genetics(parent2)?;
-        let child = self.inheritance.inherit_blended(&p1, &p2);
+        let child = self.inheritance.inherit_blended_with(&mut *self.rng(), &p1, &p2, mode, p1_weight);
         self.genetics_to_pydict(py, &child)
     }
     
-    /// Apply mutations with specified rate
-    pub fn mutate<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, rate: f32) -> PyResult<Bound<'py, PyDict>> {
+    /// Breed `count` offspring from one pair of parents in a single call
+    /// Parents are converted once; children draw from the shared RNG
+    #[pyo3(signature = (parent1, parent2, count, blended=false))]
+    pub fn inherit_litter<'py>(
+        &self,
+        py: Python<'py>,
+        parent1: &Bound<'py, PyDict>,
+        parent2: &Bound<'py, PyDict>,
+        count: usize,
+        blended: bool,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let p1 = self.pydict_to_genetics(parent1)?;
+        let p2 = self.pydict_to_genetics(parent2)?;
+        let mut rng = self.rng();
+        let children: Vec<_> = (0..count)
+            .map(|_| if blended {
+                self.inheritance.inherit_blended_with(&mut *rng, &p1, &p2, BlendMode::default(), 0.5)
+            } else {
+                self.inheritance.inherit_with(&mut *rng, &p1, &p2)
+            })
+            .collect();
+        drop(rng);
+        children.iter().map(|child| self.genetics_to_pydict(py, child)).collect()
+    }
+    
+    /// Breed the next generation from a scored population in one call
+    ///
+    /// Keeps the top `survivors` by `fitness` (one score per genome)
+    /// unchanged, then refills to the original size with mutated children
+    /// of survivor pairs. Parents are chosen by roulette selection: chance
+    /// proportional to fitness (shifted to be non-negative if any survivor
+    /// scores below zero; uniform if all are zero), with two distinct
+    /// parents unless only one survives. Draws come from the shared RNG,
+    /// so a seeded engine gives the same generation.
+    pub fn evolve<'py>(
+        &self,
+        py: Python<'py>,
+        population: Vec<Bound<'py, PyDict>>,
+        fitness: Vec<f32>,
+        survivors: usize,
+        mutation_rate: f32,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        check_rate(mutation_rate)?;
+        let population = population.iter()
+            .map(|g| self.pydict_to_genetics(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        let next = evolve_with(&mut *self.rng(), &self.inheritance, &self.mutation, &population, &fitness, survivors, mutation_rate)
+            .map_err(PyValueError::new_err)?;
+        next.iter().map(|g| self.genetics_to_pydict(py, g)).collect()
+    }
+    
+    /// Apply mutations with specified rate, which must be in [0, 1]
+    /// `macro_chance` is how often a mutated color jumps to a random one
+    /// instead of being nudged
+    #[pyo3(signature = (genetics, rate, macro_chance=DEFAULT_MACRO_CHANCE))]
+    pub fn mutate<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, rate: f32, macro_chance: f32) -> PyResult<Bound<'py, PyDict>> {
+        check_rate(rate)?;
         let genes = self.pydict_to_genetics(genetics)?;
-        let mutated = self.mutation.mutate(&genes, rate);
+        let mutated = self.mutation.mutate_with(&mut *self.rng(), &genes, rate, macro_chance);
         self.genetics_to_pydict(py, &mutated)
     }
     
+    /// Mutate a whole population in parallel, returning new dicts in order
+    ///
+    /// Genome `i` uses its own RNG seeded with `derive_seed(seed, i)`, so a
+    /// given seed reproduces the same results on any thread count. Without
+    /// `seed`, the base seed is drawn from the engine's RNG.
+    #[pyo3(signature = (population, rate, seed=None, macro_chance=DEFAULT_MACRO_CHANCE))]
+    pub fn mutate_population<'py>(
+        &self,
+        py: Python<'py>,
+        population: Vec<Bound<'py, PyDict>>,
+        rate: f32,
+        seed: Option<u64>,
+        macro_chance: f32,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        check_rate(rate)?;
+        let genomes = population.iter()
+            .map(|g| self.pydict_to_genetics(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        let seed = seed.unwrap_or_else(|| self.rng().next_u64());
+        
+        let mutation = &self.mutation;
+        let mutated = py.allow_threads(|| mutation.mutate_population(&genomes, rate, macro_chance, seed));
+        mutated.iter()
+            .map(|g| self.genetics_to_pydict(py, g))
+            .collect()
+    }
+    
+    /// Mutate exactly one named gene and return the updated dict
+    /// Raises KeyError if the gene is missing from `genetics` or not
+    /// registered
+    #[pyo3(signature = (genetics, gene_name, macro_chance=DEFAULT_MACRO_CHANCE))]
+    pub fn mutate_gene<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, gene_name: &str, macro_chance: f32) -> PyResult<Bound<'py, PyDict>> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        let mutated = self.mutation.mutate_one_with(&mut *self.rng(), &genes, gene_name, macro_chance)
+            .ok_or_else(|| PyKeyError::new_err(format!("Gene '{}' is not in the genome or not registered", gene_name)))?;
+        self.genetics_to_pydict(py, &mutated)
+    }
+    
+    /// Mutation rate for a generation: `base_rate * 0.5^(generation / half_life)`
+    /// Pass the result to `mutate`; a `half_life` of 0 means no decay
+    #[staticmethod]
+    pub fn anneal_rate(generation: u32, base_rate: f32, half_life: u32) -> f32 {
+        anneal_rate(generation, base_rate, half_life)
+    }
+    
+    /// Mutate and report what changed
+    ///
+    /// Returns `(mutated, changes)`; each change has `gene`, `type`, `old`
+    /// and `new` in their native forms (tuples for rgb, strings for
+    /// discrete), plus `delta` (new - old) for continuous genes.
+    #[pyo3(signature = (genetics, rate, macro_chance=DEFAULT_MACRO_CHANCE))]
+    pub fn mutate_with_report<'py>(
+        &self,
+        py: Python<'py>,
+        genetics: &Bound<'py, PyDict>,
+        rate: f32,
+        macro_chance: f32,
+    ) -> PyResult<(Bound<'py, PyDict>, Vec<Bound<'py, PyDict>>)> {
+        check_rate(rate)?;
+        let genes = self.pydict_to_genetics(genetics)?;
+        let (mutated, changes) = self.mutation.mutate_with_report(&mut *self.rng(), &genes, rate, macro_chance);
+        let changes = changes
+            .into_iter()
+            .map(|change| {
+                let dict = PyDict::new(py);
+                dict.set_item("type", &self.definitions.get(&change.gene).unwrap().gene_type)?;
+                dict.set_item("old", gene_to_py(py, &change.old)?)?;
+                dict.set_item("new", gene_to_py(py, &change.new)?)?;
+                if let (GeneValue::Continuous(old), GeneValue::Continuous(new)) = (&change.old, &change.new) {
+                    dict.set_item("delta", new - old)?;
+                }
+                dict.set_item("gene", change.gene)?;
+                Ok(dict)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok((self.genetics_to_pydict(py, &mutated)?, changes))
+    }
+    
+    /// Override one gene's mutation rate (None restores the global rate)
+    #[pyo3(signature = (gene_name, rate))]
+    pub fn set_gene_mutation_rate(&mut self, gene_name: &str, rate: Option<f32>) -> PyResult<()> {
+        if let Some(rate) = rate {
+            check_rate(rate)?;
+        }
+        if !self.definitions.set_mutation_rate(gene_name, rate) {
+            return Err(PyKeyError::new_err(format!("Unknown gene '{}'", gene_name)));
+        }
+        self.rebuild();
+        Ok(())
+    }
+    
+    /// Per-gene mutation likelihood: {gene: {"rate": r, "step": s}}
+    /// `rate` is the gene's override or `default_rate`; `step` is the typical
+    /// mutation size relative to the gene's range (discrete genes report 1.0)
+    #[pyo3(signature = (default_rate=DEFAULT_MUTATION_RATE))]
+    pub fn mutation_profile<'py>(&self, py: Python<'py>, default_rate: f32) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (name, (rate, step)) in self.mutation.profile(default_rate) {
+            let entry = PyDict::new(py);
+            entry.set_item("rate", rate)?;
+            entry.set_item("step", step)?;
+            dict.set_item(name, entry)?;
+        }
+        Ok(dict)
+    }
+    
+    /// Prepare a tween between two genomes for per-frame sampling
+    /// Both endpoints are parsed once; call `.at(t)` on the result
+    pub fn tween(&self, a: &Bound<'_, PyDict>, b: &Bound<'_, PyDict>) -> PyResult<GenomeTweener> {
+        let a = self.pydict_to_genetics(a)?;
+        let b = self.pydict_to_genetics(b)?;
+        Ok(GenomeTweener { inner: Tween::new(&a, &b, &self.definitions) })
+    }
+    
+    /// Get a continuous gene as (value, min, max)
+    pub fn get_continuous(&self, genetics: &Bound<'_, PyDict>, gene_name: &str) -> PyResult<(f32, f32, f32)> {
+        let def = self.definition_of_type(gene_name, "continuous")?;
+        let (min, max) = def.continuous_range.unwrap_or((f32::MIN, f32::MAX));
+        let value = self.gene_from_dict(genetics, gene_name)?
+            .as_continuous()
+            .ok_or_else(|| PyTypeError::new_err(format!("Gene '{}' is not continuous", gene_name)))?;
+        Ok((value, min, max))
+    }
+    
+    /// Get a discrete gene as (value, options)
+    pub fn get_discrete(&self, genetics: &Bound<'_, PyDict>, gene_name: &str) -> PyResult<(String, Vec<String>)> {
+        let def = self.definition_of_type(gene_name, "discrete")?;
+        let options = def.discrete_options.clone().unwrap_or_default();
+        let gene = self.gene_from_dict(genetics, gene_name)?;
+        let value = gene
+            .as_discrete()
+            .ok_or_else(|| PyTypeError::new_err(format!("Gene '{}' is not discrete", gene_name)))?;
+        Ok((value.to_string(), options))
+    }
+    
+    /// Ensure two color genes meet a WCAG contrast ratio
+    /// Adjusts the lightness of `gene_b` (or `gene_a` with `adjust="a"`)
+    /// and returns the fixed genetics; unchanged if already compliant
+    #[pyo3(signature = (genetics, gene_a, gene_b, min_ratio, adjust="b"))]
+    pub fn ensure_contrast<'py>(
+        &self,
+        py: Python<'py>,
+        genetics: &Bound<'py, PyDict>,
+        gene_a: &str,
+        gene_b: &str,
+        min_ratio: f32,
+        adjust: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        if !(1.0..=21.0).contains(&min_ratio) {
+            return Err(PyValueError::new_err("min_ratio must be between 1.0 and 21.0"));
+        }
+        let (fixed_gene, anchor_gene) = match adjust {
+            "a" => (gene_a, gene_b),
+            "b" => (gene_b, gene_a),
+            _ => return Err(PyValueError::new_err("adjust must be 'a' or 'b'")),
+        };
+        
+        let mut genes = self.pydict_to_genetics(genetics)?;
+        let color_of = |name: &str| -> PyResult<Rgb> {
+            self.definition_of_type(name, "rgb")?;
+            genes.get(name)
+                .and_then(|v| v.as_rgb())
+                .copied()
+                .ok_or_else(|| PyKeyError::new_err(format!("Gene '{}' missing from genetics", name)))
+        };
+        let fixed = color_of(fixed_gene)?;
+        let anchor = color_of(anchor_gene)?;
+        
+        genes.insert(fixed_gene.to_string(), GeneValue::Rgb(fixed.with_min_contrast(&anchor, min_ratio)));
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Racing stats implied by a genome's structural genes
+    ///
+    /// Uses the clamped mapping documented in `genetics::stats`; missing
+    /// genes count as their defaults. Keys match `PyTurtle`'s constructor,
+    /// so `PyTurtle(name, **genetics.derive_stats(g))` works directly.
+    /// Max energy is keyed "energy" here but "max_energy" in
+    /// `PyTurtle.get_stats`; stat lookups such as `genome_for_stats` and
+    /// `apply_modifier` accept either name.
+    pub fn derive_stats<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = derive_stats(&self.pydict_to_genetics(genetics)?, &self.definitions);
+        let dict = PyDict::new(py);
+        for (name, value) in stat_targets(&stats) {
+            dict.set_item(name, value)?;
+        }
+        Ok(dict)
+    }
+    
+    /// Warm/cool and brighten/darken every color gene at once
+    ///
+    /// `brightness` in [-1, 1] moves colors toward white (+) or black (-);
+    /// `warmth` in [-1, 1] raises red and lowers blue (+) or the reverse.
+    /// Non-rgb genes pass through unchanged.
+    #[pyo3(signature = (genetics, brightness=0.0, warmth=0.0))]
+    pub fn adjust_palette<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, brightness: f32, warmth: f32) -> PyResult<Bound<'py, PyDict>> {
+        if !(-1.0..=1.0).contains(&brightness) || !(-1.0..=1.0).contains(&warmth) {
+            return Err(PyValueError::new_err("brightness and warmth must be between -1 and 1"));
+        }
+        let mut genes = self.pydict_to_genetics(genetics)?;
+        for value in genes.values_mut() {
+            if let GeneValue::Rgb(rgb) = value {
+                *rgb = rgb.adjust(brightness, warmth);
+            }
+        }
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Preview a genome's colors as seen with a color vision deficiency
+    /// `mode` is "deuteranopia", "protanopia" or "tritanopia"; every rgb
+    /// gene is remapped and other genes pass through unchanged
+    #[pyo3(signature = (genetics, mode="deuteranopia"))]
+    pub fn simulate_colorblind<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>, mode: &str) -> PyResult<Bound<'py, PyDict>> {
+        let mode = CvdMode::parse(mode)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown color vision mode '{}'", mode)))?;
+        let mut genes = self.pydict_to_genetics(genetics)?;
+        for value in genes.values_mut() {
+            if let GeneValue::Rgb(rgb) = value {
+                *rgb = rgb.simulate_cvd(mode);
+            }
+        }
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Build a best-fit genome for the given target stats
+    /// Structural genes are inverted from the stat mapping and clamped to
+    /// their ranges; colors, patterns and untargeted genes are random.
+    /// Takes the keys `derive_stats` returns
+    pub fn genome_for_stats<'py>(&self, py: Python<'py>, target_stats: HashMap<String, f32>) -> PyResult<Bound<'py, PyDict>> {
+        let genetics = genome_for_stats(&target_stats, &self.definitions, &mut *self.rng());
+        self.genetics_to_pydict(py, &genetics)
+    }
+    
+    /// Copy of a genome with continuous genes clamped to their ranges and
+    /// unknown discrete options replaced by the default
+    /// Applies regardless of `clamp_on_import`
+    pub fn sanitize<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+        let genes = self.read_genetics(genetics, true)?;
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Canonical form: all registry genes present, continuous values
+    /// quantized, keys in sorted order
+    pub fn canonicalize<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        self.genetics_to_pydict(py, &self.definitions.canonicalize(&genes))
+    }
+    
+    /// Stable hex fingerprint of a genome for deduplication
+    ///
+    /// A 64-bit FNV-1a hash of the sanitized, canonical genome. Continuous
+    /// genes are rounded to 1e-4 first, since exact float equality is
+    /// fragile; genomes equal after `sanitize` share a fingerprint.
+    pub fn fingerprint(&self, genetics: &Bound<'_, PyDict>) -> PyResult<String> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        Ok(format!("{:016x}", self.definitions.fingerprint(&genes)))
+    }
+    
+    /// Display-ready view of a genome for the renderer
+    ///
+    /// Returns `shell`, `body`, `head`, `legs` and `eyes` sub-dicts (plus
+    /// `other` for genes without a known prefix) with missing genes
+    /// defaulted and colors as `#rrggbb`. Keys drop the group prefix, and
+    /// `pattern_color` is folded into `shell.pattern_color`.
+    pub fn to_phenotype<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        let dict = PyDict::new(py);
+        for (group, traits) in phenotype(&genes, &self.definitions) {
+            let sub = PyDict::new(py);
+            for (key, value) in traits {
+                match value {
+                    GeneValue::Rgb(rgb) => sub.set_item(key, rgb.to_hex())?,
+                    other => sub.set_item(key, gene_to_py(py, &other)?)?,
+                }
+            }
+            dict.set_item(group, sub)?;
+        }
+        Ok(dict)
+    }
+    
+    /// Suggest a flavor name from shell color and pattern
+    /// Deterministic for a given genome and seed
+    pub fn suggest_name(&self, genetics: &Bound<'_, PyDict>, seed: u64) -> PyResult<String> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        Ok(suggest_name(&genes, &mut rng::seeded(seed)))
+    }
+    
+    /// Serialize genetics to JSON, tagging each gene with its type
+    /// Genes are written in sorted order so equal genomes give equal strings
+    pub fn to_json(&self, genetics: &Bound<'_, PyDict>) -> PyResult<String> {
+        let genes: BTreeMap<String, GeneValue> = self.pydict_to_genetics(genetics)?.into_iter().collect();
+        serde_json::to_string(&genes)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+    
+    /// Parse genetics written by `to_json` back into a dict
+    pub fn from_json<'py>(&self, py: Python<'py>, s: &str) -> PyResult<Bound<'py, PyDict>> {
+        let genes: BTreeMap<String, GeneValue> = serde_json::from_str(s)
+            .map_err(|e| PyValueError::new_err(format!("Invalid genetics JSON: {}", e)))?;
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Serialize genetics to a compact binary blob
+    ///
+    /// Much smaller than `to_json` (gene names plus 3-5 byte values) and
+    /// lossless, keeping exact f32 bits. Genes are written in registry
+    /// order behind a short header; see `from_bytes`.
+    pub fn to_bytes<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyBytes>> {
+        let genes = self.pydict_to_genetics(genetics)?;
+        let bytes = to_bytes(&genes, &self.definitions).map_err(PyValueError::new_err)?;
+        Ok(PyBytes::new(py, &bytes))
+    }
+    
+    /// Parse a blob written by `to_bytes` back into a dict
+    /// Entries of kinds added by later format versions are skipped
+    pub fn from_bytes<'py>(&self, py: Python<'py>, b: &[u8]) -> PyResult<Bound<'py, PyDict>> {
+        let genes = from_bytes(b)
+            .map_err(|e| PyValueError::new_err(format!("Invalid genetics bytes: {}", e)))?;
+        self.genetics_to_pydict(py, &genes)
+    }
+    
+    /// Check a genetics dict against the registry without changing it
+    ///
+    /// Returns `unknown_keys`, `type_errors` as (name, expected, got),
+    /// `out_of_range` as (name, value, (min, max)) and `invalid_options`
+    /// as (name, value); all lists are empty for a clean genome.
+    pub fn validate<'py>(&self, py: Python<'py>, genetics: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyDict>> {
+        let report = validate(&pydict_to_raw(genetics)?, &self.definitions);
+        let dict = PyDict::new(py);
+        dict.set_item("unknown_keys", report.unknown_keys)?;
+        dict.set_item("type_errors", report.type_errors)?;
+        dict.set_item("out_of_range", report.out_of_range)?;
+        dict.set_item("invalid_options", report.invalid_options)?;
+        Ok(dict)
+    }
+    
+    /// Validate many genomes at once
+    /// Returns one problem list per genome, in input order (empty = clean).
+    /// Checks run in parallel with the GIL released
+    pub fn validate_batch(&self, py: Python<'_>, genomes: Vec<Bound<'_, PyDict>>) -> PyResult<Vec<Vec<String>>> {
+        let raw = genomes.iter()
+            .map(|g| pydict_to_raw(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        
+        let definitions = &self.definitions;
+        Ok(py.allow_threads(|| validate_batch(&raw, definitions)))
+    }
+    
+    /// Format an `(r, g, b)` color as `#rrggbb`
+    #[staticmethod]
+    pub fn color_to_hex(color: (u8, u8, u8)) -> String {
+        Rgb::from_tuple(color).to_hex()
+    }
+    
+    /// Parse `#rrggbb` or `#rgb` (leading `#` optional) into `(r, g, b)`
+    #[staticmethod]
+    pub fn color_from_hex(hex: &str) -> PyResult<(u8, u8, u8)> {
+        Rgb::from_hex(hex)
+            .map(|rgb| rgb.to_tuple())
+            .map_err(PyValueError::new_err)
+    }
+    
     /// Calculate genetic similarity (0.0 to 1.0)
     pub fn similarity(&self, genetics1: &Bound<'_, PyDict>, genetics2: &Bound<'_, PyDict>) -> PyResult<f32> {
         let g1 = self.pydict_to_genetics(genetics1)?;
         let g2 = self.pydict_to_genetics(genetics2)?;
         Ok(self.inheritance.calculate_similarity(&g1, &g2))
     }
+    
+    /// Similarity of one query genome to each of many others
+    /// Every dict is converted once; comparisons run in parallel with the
+    /// GIL released. Results are in the same order as `others`
+    pub fn similarity_to_all(&self, py: Python<'_>, query: &Bound<'_, PyDict>, others: Vec<Bound<'_, PyDict>>) -> PyResult<Vec<f32>> {
+        let query = self.pydict_to_genetics(query)?;
+        let others = others.iter()
+            .map(|g| self.pydict_to_genetics(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        
+        let inheritance = &self.inheritance;
+        Ok(py.allow_threads(|| similarity_to_all(&query, &others, inheritance)))
+    }
+    
+    /// Full pairwise similarity matrix of a population
+    ///
+    /// Symmetric with 1.0 on the diagonal; each pair is computed once, in
+    /// parallel with the GIL released. The result holds n² floats, so a
+    /// population of 10,000 is already ~100 million values.
+    pub fn similarity_matrix(&self, py: Python<'_>, population: Vec<Bound<'_, PyDict>>) -> PyResult<Vec<Vec<f32>>> {
+        let population = population.iter()
+            .map(|g| self.pydict_to_genetics(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        
+        let inheritance = &self.inheritance;
+        Ok(py.allow_threads(|| {
+            let n = population.len();
+            // Upper triangle, row by row: row i holds pairs (i, j) for j > i
+            let upper: Vec<Vec<f32>> = (0..n).into_par_iter()
+                .map(|i| {
+                    population[i + 1..].iter()
+                        .map(|g| inheritance.calculate_similarity(&population[i], g))
+                        .collect()
+                })
+                .collect();
+            
+            let mut matrix = vec![vec![1.0; n]; n];
+            for (i, row) in upper.iter().enumerate() {
+                for (offset, &value) in row.iter().enumerate() {
+                    let j = i + 1 + offset;
+                    matrix[i][j] = value;
+                    matrix[j][i] = value;
+                }
+            }
+            matrix
+        }))
+    }
+    
+    /// Approximate inbreeding coefficient (0.0 to 1.0) of two potential mates
+    ///
+    /// Each list holds a mate's known ancestors laid out by generation:
+    /// parents at 0-1, grandparents at 2-5, then 6-13 and so on. The result
+    /// is the mean similarity of cross-mate ancestor pairs from the same
+    /// generation, a proxy for relatedness rather than a pedigree-path
+    /// coefficient. Returns 0.0 if either list is empty.
+    pub fn inbreeding_coefficient(&self, ancestors1: Vec<Bound<'_, PyDict>>, ancestors2: Vec<Bound<'_, PyDict>>) -> PyResult<f32> {
+        let pedigree = |ancestors: Vec<Bound<'_, PyDict>>| -> PyResult<Pedigree> {
+            let genomes = ancestors.iter()
+                .map(|g| self.pydict_to_genetics(g))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(Pedigree::from_ancestors(genomes))
+        };
+        let first = pedigree(ancestors1)?;
+        let second = pedigree(ancestors2)?;
+        Ok(first.inbreeding_coefficient(&second, &self.inheritance))
+    }
+    
+    /// Mean pairwise genetic distance (`1 - similarity`) of a population
+    ///
+    /// Returns 0.0 for fewer than two individuals. With `detailed=True`
+    /// returns `(diversity, per_gene)`, where `per_gene` maps each gene to
+    /// its variance (continuous; rgb channels scaled to [0, 1]) or allele
+    /// entropy in bits (discrete).
+    #[pyo3(signature = (population, detailed=false))]
+    pub fn population_diversity(&self, py: Python<'_>, population: Vec<Bound<'_, PyDict>>, detailed: bool) -> PyResult<PyObject> {
+        let population = population.iter()
+            .map(|g| self.pydict_to_genetics(g))
+            .collect::<PyResult<Vec<_>>>()?;
+        
+        let inheritance = &self.inheritance;
+        let diversity = py.allow_threads(|| population_diversity(&population, inheritance));
+        if detailed {
+            (diversity, gene_spread(&population, &self.definitions)).into_py_any(py)
+        } else {
+            diversity.into_py_any(py)
+        }
+    }
+    
+    /// Breed one child per parent pair and estimate heritability
+    ///
+    /// `mode` is "blended" (default), "mendelian" or "crossover". Returns,
+    /// per continuous gene, the correlation in [-1, 1] between mid-parent
+    /// and child value: blending tracks parents almost exactly, while
+    /// Mendelian picks land lower. Genes with fewer than two usable pairs
+    /// or no variation are left out. Children draw from the shared RNG.
+    #[pyo3(signature = (parent_pairs, mode="blended"))]
+    pub fn heritability_estimate(
+        &self,
+        parent_pairs: Vec<(Bound<'_, PyDict>, Bound<'_, PyDict>)>,
+        mode: &str,
+    ) -> PyResult<BTreeMap<String, f32>> {
+        let parents = parent_pairs.iter()
+            .map(|(p1, p2)| Ok((self.pydict_to_genetics(p1)?, self.pydict_to_genetics(p2)?)))
+            .collect::<PyResult<Vec<_>>>()?;
+        
+        let mut rng = self.rng();
+        let children: Vec<_> = match mode {
+            "blended" => parents.iter()
+                .map(|(p1, p2)| self.inheritance.inherit_blended_with(&mut *rng, p1, p2, BlendMode::default(), 0.5))
+                .collect(),
+            "mendelian" => parents.iter()
+                .map(|(p1, p2)| self.inheritance.inherit_with(&mut *rng, p1, p2))
+                .collect(),
+            "crossover" => parents.iter()
+                .map(|(p1, p2)| self.inheritance.inherit_crossover_with(&mut *rng, p1, p2, 1))
+                .collect(),
+            _ => return Err(PyValueError::new_err(format!("Unknown inheritance mode '{}'", mode))),
+        };
+        Ok(heritability(&parents, &children, &self.definitions))
+    }
+}
+
+/// Interpolator between two genomes, returned by `PyGenetics.tween`
+#[pyclass]
+pub struct GenomeTweener {
+    inner: Tween,
+}
+
+#[pymethods]
+impl GenomeTweener {
+    /// Genome at `t` in [0, 1]: 0 is the first endpoint, 1 the second
+    pub fn at<'py>(&self, py: Python<'py>, t: f32) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (name, value) in self.inner.at(t) {
+            dict.set_item(name, gene_to_py(py, &value)?)?;
+        }
+        Ok(dict)
+    }
+}
+
+impl Default for PyGenetics {
+    fn default() -> Self {
+        Self::new(None)
+    }
 }
 
 impl PyGenetics {
+    /// Lock the shared RNG for the draws of one call
+    fn rng(&self) -> MutexGuard<'_, rng::SeededRng> {
+        self.rng.lock().unwrap()
+    }
+    
+    /// Rebuild the inheritance, mutation and preset helpers after the
+    /// registry changes
+    fn rebuild(&mut self) {
+        self.inheritance = Inheritance::new(self.definitions.clone());
+        self.mutation = Mutation::new(self.definitions.clone());
+        self.presets = Presets::new(&self.definitions);
+    }
+    
+    /// Look up a registered gene, checking its type
+    fn definition_of_type(&self, gene_name: &str, gene_type: &str) -> PyResult<&GeneDefinition> {
+        let def = self.definitions.get(gene_name)
+            .ok_or_else(|| PyKeyError::new_err(format!("Unknown gene '{}'", gene_name)))?;
+        if def.gene_type != gene_type {
+            return Err(PyTypeError::new_err(format!(
+                "Gene '{}' is {}, not {}", gene_name, def.gene_type, gene_type
+            )));
+        }
+        Ok(def)
+    }
+    
+    /// Extract a single gene from a Python dict
+    fn gene_from_dict(&self, dict: &Bound<'_, PyDict>, gene_name: &str) -> PyResult<GeneValue> {
+        let genetics = self.pydict_to_genetics(dict)?;
+        genetics.get(gene_name)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err(format!("Gene '{}' missing from genetics", gene_name)))
+    }
+    
     /// Convert Python dict to Rust HashMap
+    /// Values are sanitized when `clamp_on_import` is set
     fn pydict_to_genetics(&self, dict: &Bound<'_, PyDict>) -> PyResult<HashMap<String, GeneValue>> {
+        self.read_genetics(dict, self.clamp_on_import)
+    }
+    
+    /// Convert Python dict to Rust HashMap, optionally sanitizing each gene
+    fn read_genetics(&self, dict: &Bound<'_, PyDict>, clamp: bool) -> PyResult<HashMap<String, GeneValue>> {
         let mut genetics = HashMap::new();
         
         for (key, value) in dict.iter() {
@@ -103,6 +1139,7 @@ impl PyGenetics {
                     },
                     _ => continue,
                 };
+                let gene_value = if clamp { def.sanitize(gene_value) } else { gene_value };
                 genetics.insert(key_str, gene_value);
             }
         }
@@ -110,24 +1147,53 @@ impl PyGenetics {
         Ok(genetics)
     }
     
-    /// Convert Rust HashMap to Python dict
-    fn genetics_to_pydict<'py>(&self, py: Python<'py>, genetics: &HashMap<String, GeneValue>) -> PyResult<Bound<'py, PyDict>> {
+    /// Convert Rust gene map to Python dict, preserving iteration order
+    fn genetics_to_pydict<'py, 'a>(
+        &self,
+        py: Python<'py>,
+        genetics: impl IntoIterator<Item = (&'a String, &'a GeneValue)>,
+    ) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
         
         for (key, value) in genetics {
-            match value {
-                GeneValue::Rgb(rgb) => {
-                    dict.set_item(key, rgb.to_tuple())?;
-                },
-                GeneValue::Discrete(s) => {
-                    dict.set_item(key, s)?;
-                },
-                GeneValue::Continuous(f) => {
-                    dict.set_item(key, f)?;
-                },
-            }
+            dict.set_item(key, gene_to_py(py, value)?)?;
         }
         
         Ok(dict)
     }
 }
+
+/// `validate_rate` as a Python ValueError
+fn check_rate(rate: f32) -> PyResult<()> {
+    validate_rate(rate).map_err(PyValueError::new_err)
+}
+
+/// Read a Python dict into raw values without consulting the registry
+fn pydict_to_raw(dict: &Bound<'_, PyDict>) -> PyResult<HashMap<String, RawGene>> {
+    let mut raw = HashMap::new();
+    
+    for (key, value) in dict.iter() {
+        let key_str: String = key.extract()?;
+        let gene = if let Ok(tuple) = value.extract::<(u8, u8, u8)>() {
+            RawGene::Rgb(Rgb::from_tuple(tuple))
+        } else if let Ok(s) = value.extract::<String>() {
+            RawGene::Text(s)
+        } else if let Ok(f) = value.extract::<f32>() {
+            RawGene::Number(f)
+        } else {
+            RawGene::Other(value.get_type().name()?.to_string())
+        };
+        raw.insert(key_str, gene);
+    }
+    
+    Ok(raw)
+}
+
+/// Convert a single gene value to its native Python form
+fn gene_to_py(py: Python<'_>, value: &GeneValue) -> PyResult<PyObject> {
+    match value {
+        GeneValue::Rgb(rgb) => rgb.to_tuple().into_py_any(py),
+        GeneValue::Discrete(s) => s.into_py_any(py),
+        GeneValue::Continuous(f) => f.into_py_any(py),
+    }
+}
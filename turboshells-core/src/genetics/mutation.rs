@@ -1,58 +1,205 @@
 //! Mutation system - genetic variations
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use rand::Rng;
-use rand::distributions::{Distribution, Standard};
+use rayon::prelude::*;
+use crate::rng;
 use crate::types::{GeneValue, Rgb};
-use super::genes::GeneDefinitions;
+use super::genes::{GeneDefinition, GeneDefinitions};
+
+/// Mutation rate assumed for genes without an override
+pub const DEFAULT_MUTATION_RATE: f32 = 0.1;
+
+/// Chance an rgb mutation replaces the color outright instead of nudging it
+pub const DEFAULT_MACRO_CHANCE: f32 = 0.05;
+
+/// Max per-channel nudge applied to a mutated color
+const RGB_STEP: i16 = 30;
+
+/// Typical size of one mutation relative to the gene's full range
+///
+/// rgb: 30/255 per channel; continuous: the gene's mutation strength (one
+/// std dev, 0.1 by default); discrete: 1.0, since a mutation always jumps
+/// to a different option.
+pub fn step_size(def: &GeneDefinition) -> f32 {
+    match def.gene_type.as_str() {
+        "rgb" => RGB_STEP as f32 / 255.0,
+        "continuous" => def.continuous_mutation_strength,
+        "discrete" => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// A gene whose value a mutation changed
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneChange {
+    pub gene: String,
+    pub old: GeneValue,
+    pub new: GeneValue,
+}
+
+/// Reject mutation rates outside [0, 1], which would otherwise silently
+/// mutate everything (above 1) or nothing (below 0)
+pub fn validate_rate(rate: f32) -> Result<(), String> {
+    if (0.0..=1.0).contains(&rate) {
+        Ok(())
+    } else {
+        Err(format!("Mutation rate must be in [0, 1], got {}", rate))
+    }
+}
+
+/// Mutation rate decayed by generation: `base_rate * 0.5^(generation / half_life)`
+///
+/// The rate halves every `half_life` generations, smoothly in between.
+/// A `half_life` of 0 means no decay.
+pub fn anneal_rate(generation: u32, base_rate: f32, half_life: u32) -> f32 {
+    if half_life == 0 {
+        return base_rate;
+    }
+    base_rate * 0.5_f32.powf(generation as f32 / half_life as f32)
+}
+
+/// Fold a value back into `range` by mirroring it off the bounds
+fn reflect(value: f32, range: (f32, f32)) -> f32 {
+    let span = range.1 - range.0;
+    if span <= 0.0 || !value.is_finite() {
+        return value.clamp(range.0, range.1);
+    }
+    let offset = (value - range.0).rem_euclid(2.0 * span);
+    let folded = if offset > span { 2.0 * span - offset } else { offset };
+    range.0 + folded
+}
 
 /// Implements genetic mutations
 pub struct Mutation {
     definitions: GeneDefinitions,
+    /// Registered gene names in sorted order, the order genes are visited
+    names: Vec<String>,
 }
 
 impl Mutation {
     pub fn new(definitions: GeneDefinitions) -> Self {
-        Self { definitions }
+        let names = definitions.names().into_iter().cloned().collect();
+        Self { definitions, names }
     }
     
     /// Apply mutations with specified rate (0.0 to 1.0)
+    /// Genes with a per-gene `mutation_rate` use that instead
     pub fn mutate(
         &self,
         genetics: &HashMap<String, GeneValue>,
         rate: f32,
     ) -> HashMap<String, GeneValue> {
-        let mut rng = rand::thread_rng();
+        self.mutate_with(&mut rand::thread_rng(), genetics, rate, DEFAULT_MACRO_CHANCE)
+    }
+    
+    /// Apply mutations drawing from the given RNG
+    /// Genes are visited in sorted order so seeded results are reproducible.
+    /// A mutated rgb gene jumps to a fully random color with `macro_chance`
+    pub fn mutate_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        genetics: &HashMap<String, GeneValue>,
+        rate: f32,
+        macro_chance: f32,
+    ) -> HashMap<String, GeneValue> {
+        self.mutate_with_report(rng, genetics, rate, macro_chance).0
+    }
+    
+    /// Mutate every genome of a population in parallel
+    ///
+    /// Genome `i` draws from its own stream `derive_seed(seed, i)`, so the
+    /// result depends only on `seed` and the input order, never on how
+    /// rayon schedules the work.
+    pub fn mutate_population(
+        &self,
+        population: &[HashMap<String, GeneValue>],
+        rate: f32,
+        macro_chance: f32,
+        seed: u64,
+    ) -> Vec<HashMap<String, GeneValue>> {
+        population.par_iter()
+            .enumerate()
+            .map(|(i, genetics)| {
+                let mut rng = rng::seeded(rng::derive_seed(seed, i as u64));
+                self.mutate_with(&mut rng, genetics, rate, macro_chance)
+            })
+            .collect()
+    }
+    
+    /// Apply mutations and list every gene whose value actually changed,
+    /// in sorted order (a roll that lands on the same value is not listed)
+    pub fn mutate_with_report<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        genetics: &HashMap<String, GeneValue>,
+        rate: f32,
+        macro_chance: f32,
+    ) -> (HashMap<String, GeneValue>, Vec<GeneChange>) {
         let mut mutated = genetics.clone();
+        let mut changes = Vec::new();
         
-        for (name, value) in genetics {
-            if rng.gen::<f32>() < rate {
-                if let Some(def) = self.definitions.get(name) {
-                    let new_value = self.mutate_gene(value, def);
-                    mutated.insert(name.clone(), new_value);
+        for name in &self.names {
+            let Some(value) = genetics.get(name) else {
+                continue;
+            };
+            let def = self.definitions.get(name).unwrap();
+            if rng.gen::<f32>() < def.mutation_rate.unwrap_or(rate) {
+                let new_value = self.mutate_gene(rng, value, def, macro_chance);
+                if new_value != *value {
+                    changes.push(GeneChange {
+                        gene: name.clone(),
+                        old: value.clone(),
+                        new: new_value.clone(),
+                    });
                 }
+                mutated.insert(name.clone(), new_value);
             }
         }
         
-        mutated
+        (mutated, changes)
+    }
+    
+    /// Mutate exactly one named gene, ignoring mutation rates
+    /// None if the gene is missing from the genome or not registered
+    pub fn mutate_one_with<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        genetics: &HashMap<String, GeneValue>,
+        name: &str,
+        macro_chance: f32,
+    ) -> Option<HashMap<String, GeneValue>> {
+        let def = self.definitions.get(name)?;
+        let value = genetics.get(name)?;
+        let mut mutated = genetics.clone();
+        mutated.insert(name.to_string(), self.mutate_gene(rng, value, def, macro_chance));
+        Some(mutated)
+    }
+    
+    /// Effective (rate, step size) per gene, in sorted order
+    /// Genes without an override report `default_rate`
+    pub fn profile(&self, default_rate: f32) -> BTreeMap<String, (f32, f32)> {
+        self.definitions.names()
+            .into_iter()
+            .map(|name| {
+                let def = self.definitions.get(name).unwrap();
+                (name.clone(), (def.mutation_rate.unwrap_or(default_rate), step_size(def)))
+            })
+            .collect()
     }
     
     /// Mutate a single gene value
-    fn mutate_gene(&self, value: &GeneValue, def: &super::genes::GeneDefinition) -> GeneValue {
+    fn mutate_gene<R: Rng + ?Sized>(&self, rng: &mut R, value: &GeneValue, def: &GeneDefinition, macro_chance: f32) -> GeneValue {
         match (value, def.gene_type.as_str()) {
             (GeneValue::Rgb(rgb), "rgb") => {
-                GeneValue::Rgb(self.mutate_rgb(rgb))
+                GeneValue::Rgb(self.mutate_rgb(rng, rgb, macro_chance))
             },
             (GeneValue::Discrete(s), "discrete") => {
-                if let Some(options) = &def.discrete_options {
-                    GeneValue::Discrete(self.mutate_discrete(s, options))
-                } else {
-                    value.clone()
-                }
+                GeneValue::Discrete(self.mutate_discrete(rng, s, def))
             },
             (GeneValue::Continuous(f), "continuous") => {
                 if let Some(range) = def.continuous_range {
-                    GeneValue::Continuous(self.mutate_continuous(*f, range))
+                    GeneValue::Continuous(self.mutate_continuous(rng, *f, range, def.continuous_mutation_strength))
                 } else {
                     value.clone()
                 }
@@ -61,32 +208,34 @@ impl Mutation {
         }
     }
     
-    /// Mutate RGB color with slight variations
-    fn mutate_rgb(&self, color: &Rgb) -> Rgb {
-        let mut rng = rand::thread_rng();
+    /// Mutate RGB color with slight variations, or rarely a whole new color
+    fn mutate_rgb<R: Rng + ?Sized>(&self, rng: &mut R, color: &Rgb, macro_chance: f32) -> Rgb {
+        if rng.gen::<f32>() < macro_chance {
+            return Rgb::new(rng.gen(), rng.gen(), rng.gen());
+        }
         Rgb {
-            r: (color.r as i16 + rng.gen_range(-30..=30)).clamp(0, 255) as u8,
-            g: (color.g as i16 + rng.gen_range(-30..=30)).clamp(0, 255) as u8,
-            b: (color.b as i16 + rng.gen_range(-30..=30)).clamp(0, 255) as u8,
+            r: (color.r as i16 + rng.gen_range(-RGB_STEP..=RGB_STEP)).clamp(0, 255) as u8,
+            g: (color.g as i16 + rng.gen_range(-RGB_STEP..=RGB_STEP)).clamp(0, 255) as u8,
+            b: (color.b as i16 + rng.gen_range(-RGB_STEP..=RGB_STEP)).clamp(0, 255) as u8,
         }
     }
     
-    /// Mutate discrete value by selecting a different option
-    fn mutate_discrete(&self, current: &str, options: &[String]) -> String {
-        let mut rng = rand::thread_rng();
-        let available: Vec<_> = options.iter().filter(|o| *o != current).collect();
-        if available.is_empty() {
-            current.to_string()
-        } else {
-            available[rng.gen_range(0..available.len())].clone()
-        }
+    /// Mutate discrete value by selecting a different option, weighted by
+    /// the gene's `discrete_weights` if it has them
+    fn mutate_discrete<R: Rng + ?Sized>(&self, rng: &mut R, current: &str, def: &GeneDefinition) -> String {
+        def.pick_option(rng, Some(current))
+            .unwrap_or_else(|| current.to_string())
     }
     
     /// Mutate continuous value with gaussian noise
-    fn mutate_continuous(&self, value: f32, range: (f32, f32)) -> f32 {
-        let mut rng = rand::thread_rng();
+    ///
+    /// `strength` is the std dev as a fraction of the range. Results past
+    /// a bound are reflected back inside (a step 0.05 over the max lands
+    /// 0.05 below it) rather than clamped, so values don't pile up on the
+    /// edges.
+    fn mutate_continuous<R: Rng + ?Sized>(&self, rng: &mut R, value: f32, range: (f32, f32), strength: f32) -> f32 {
         let range_size = range.1 - range.0;
-        let mutation_strength = range_size * 0.1;  // 10% of range
+        let mutation_strength = range_size * strength;
         
         // Gaussian mutation using Box-Muller transform
         let u1: f32 = rng.gen();
@@ -94,7 +243,7 @@ impl Mutation {
         let normal = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
         
         let mutation = normal * mutation_strength;
-        (value + mutation).clamp(range.0, range.1)
+        reflect(value + mutation, range)
     }
     
     /// Adaptive mutation based on parent similarity
@@ -104,7 +253,7 @@ impl Mutation {
         similarity: f32,
     ) -> HashMap<String, GeneValue> {
         // Higher similarity = higher mutation rate
-        let rate = if similarity > 0.9 {
+        let rate: f32 = if similarity > 0.9 {
             0.3
         } else if similarity > 0.7 {
             0.2
@@ -114,6 +263,46 @@ impl Mutation {
             0.05
         };
         
-        self.mutate(genetics, rate)
+        self.mutate(genetics, rate.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn zero_rate_gene_shows_zero_in_profile_and_never_mutates() {
+        let mut definitions = GeneDefinitions::new();
+        assert!(definitions.set_mutation_rate("leg_length", Some(0.0)));
+        let mutation = Mutation::new(definitions.clone());
+        
+        let profile = mutation.profile(DEFAULT_MUTATION_RATE);
+        assert_eq!(profile["leg_length"].0, 0.0);
+        assert_eq!(profile["head_size_modifier"].0, DEFAULT_MUTATION_RATE);
+        
+        let genome = definitions.get_defaults();
+        let mut rng = rng::seeded(4);
+        for _ in 0..100 {
+            let mutated = mutation.mutate_with(&mut rng, &genome, 1.0, DEFAULT_MACRO_CHANCE);
+            assert_eq!(mutated["leg_length"], genome["leg_length"]);
+        }
+    }
+    
+    #[test]
+    fn anneal_rate_halves_per_half_life_and_zero_disables_decay() {
+        assert_eq!(anneal_rate(0, 0.2, 10), 0.2);
+        assert!((anneal_rate(10, 0.2, 10) - 0.1).abs() < 1e-6);
+        assert!((anneal_rate(20, 0.2, 10) - 0.05).abs() < 1e-6);
+        assert_eq!(anneal_rate(1000, 0.2, 0), 0.2);
+    }
+    
+    #[test]
+    fn validate_rate_accepts_the_closed_unit_interval() {
+        assert!(validate_rate(0.0).is_ok());
+        assert!(validate_rate(1.0).is_ok());
+        assert!(validate_rate(-0.1).is_err());
+        assert!(validate_rate(1.1).is_err());
+        assert!(validate_rate(f32::NAN).is_err());
     }
 }
@@ -0,0 +1,91 @@
+//! Flavor name generation from genetics
+
+use std::collections::HashMap;
+use rand::Rng;
+use crate::types::{GeneValue, Rgb};
+
+const ADJECTIVES: [&str; 12] = [
+    "Swift", "Sleepy", "Brave", "Lucky", "Grumpy", "Gentle",
+    "Mighty", "Tiny", "Wandering", "Jolly", "Sly", "Steady",
+];
+
+/// Word describing a color: grays by lightness, otherwise by hue band
+fn color_word(color: &Rgb) -> &'static str {
+    let (h, s, l) = color.to_hsl();
+    if l < 0.12 {
+        return "Sooty";
+    }
+    if l > 0.9 {
+        return "Pale";
+    }
+    if s < 0.15 {
+        return "Slate";
+    }
+    match h {
+        h if h < 15.0 => "Ember",
+        h if h < 45.0 => "Amber",
+        h if h < 70.0 => "Golden",
+        h if h < 160.0 => "Mossy",
+        h if h < 200.0 => "Tidal",
+        h if h < 255.0 => "Azure",
+        h if h < 290.0 => "Violet",
+        h if h < 335.0 => "Rosy",
+        _ => "Ember",
+    }
+}
+
+/// Word describing a shell pattern type
+fn pattern_word(pattern: &str) -> &'static str {
+    match pattern {
+        "hex" => "Hex",
+        "spots" => "Speckle",
+        "stripes" => "Stripe",
+        "rings" => "Ring",
+        _ => "Shell",
+    }
+}
+
+/// Suggest a name as "<Adjective> <Color> <Pattern>"
+///
+/// The color word comes from `shell_base_color`, the pattern word from
+/// `shell_pattern_type`, and the adjective is drawn from `rng`, so the
+/// result is deterministic for a given genome and seed.
+pub fn suggest_name<R: Rng + ?Sized>(genetics: &HashMap<String, GeneValue>, rng: &mut R) -> String {
+    let color = genetics.get("shell_base_color")
+        .and_then(|v| v.as_rgb())
+        .copied()
+        .unwrap_or_default();
+    let pattern = genetics.get("shell_pattern_type")
+        .and_then(|v| v.as_discrete())
+        .unwrap_or("");
+    let adjective = ADJECTIVES[rng.gen_range(0..ADJECTIVES.len())];
+    
+    format!("{} {} {}", adjective, color_word(&color), pattern_word(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    
+    fn genome(color: Rgb) -> HashMap<String, GeneValue> {
+        HashMap::from([
+            ("shell_base_color".to_string(), GeneValue::Rgb(color)),
+            ("shell_pattern_type".to_string(), GeneValue::Discrete("spots".to_string())),
+        ])
+    }
+    
+    #[test]
+    fn distinct_colors_get_distinct_color_words() {
+        let green = suggest_name(&genome(Rgb::new(34, 139, 34)), &mut rng::seeded(1));
+        let blue = suggest_name(&genome(Rgb::new(30, 60, 200)), &mut rng::seeded(1));
+        assert!(green.ends_with("Mossy Speckle"), "{}", green);
+        assert!(blue.ends_with("Azure Speckle"), "{}", blue);
+    }
+    
+    #[test]
+    fn same_genome_and_seed_give_the_same_name() {
+        let g = genome(Rgb::new(200, 40, 40));
+        assert_eq!(suggest_name(&g, &mut rng::seeded(8)), suggest_name(&g, &mut rng::seeded(8)));
+    }
+}
@@ -0,0 +1,46 @@
+//! Pedigrees and an approximate inbreeding coefficient
+
+use std::collections::HashMap;
+use crate::types::GeneValue;
+use super::inheritance::Inheritance;
+
+/// A mate's known ancestors grouped by generation (parents first)
+#[derive(Clone, Debug, Default)]
+pub struct Pedigree {
+    pub generations: Vec<Vec<HashMap<String, GeneValue>>>,
+}
+
+impl Pedigree {
+    /// Group a flat ancestor list laid out like a binary heap: indices 0-1
+    /// are parents, 2-5 grandparents, 6-13 great-grandparents, and so on
+    pub fn from_ancestors(ancestors: Vec<HashMap<String, GeneValue>>) -> Self {
+        let mut generations: Vec<Vec<HashMap<String, GeneValue>>> = Vec::new();
+        let mut size = 2;
+        let mut ancestors = ancestors.into_iter().peekable();
+        while ancestors.peek().is_some() {
+            generations.push(ancestors.by_ref().take(size).collect());
+            size *= 2;
+        }
+        Self { generations }
+    }
+    
+    /// Proxy inbreeding coefficient for a pairing of two pedigrees
+    ///
+    /// Not a true path-counting coefficient (there are no identities to
+    /// trace): it is the mean `calculate_similarity` over every cross-mate
+    /// pair of ancestors in the same generation, in [0, 1]. Generations
+    /// only one side knows are ignored; 0.0 if no generation is shared.
+    pub fn inbreeding_coefficient(&self, other: &Pedigree, inheritance: &Inheritance) -> f32 {
+        let mut total = 0.0;
+        let mut pairs = 0usize;
+        for (mine, theirs) in self.generations.iter().zip(&other.generations) {
+            for a in mine {
+                for b in theirs {
+                    total += inheritance.calculate_similarity(a, b);
+                    pairs += 1;
+                }
+            }
+        }
+        if pairs == 0 { 0.0 } else { (total / pairs as f32).clamp(0.0, 1.0) }
+    }
+}
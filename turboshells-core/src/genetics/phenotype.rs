@@ -0,0 +1,62 @@
+//! Phenotype - display-ready view of a genome for the renderer
+
+use std::collections::{BTreeMap, HashMap};
+use crate::types::GeneValue;
+use super::genes::GeneDefinitions;
+
+/// Renderer alias collapsed into its canonical gene
+const PATTERN_COLOR_ALIAS: &str = "pattern_color";
+const PATTERN_COLOR_GENE: &str = "shell_pattern_color";
+
+/// Gene name prefixes and the body part group each one lands in
+const PREFIX_GROUPS: [(&str, &str); 6] = [
+    ("shell_", "shell"),
+    ("body_", "body"),
+    ("head_", "head"),
+    ("leg_", "legs"),
+    ("limb_", "legs"),
+    ("eye_", "eyes"),
+];
+
+/// Group that catches genes without a known prefix
+const OTHER_GROUP: &str = "other";
+
+/// Body part groups of a genome, keyed by group then trait name
+///
+/// Every registered gene is present, with missing ones defaulted. A gene
+/// lands in the group of its first matching prefix (`shell_`, `body_`,
+/// `head_`, `leg_`/`limb_`, `eye_`) keyed by the name without that
+/// prefix, so `leg_length` becomes `legs.length` and `limb_shape` becomes
+/// `legs.shape`. Genes without a known prefix go to `other` under their
+/// full name.
+///
+/// `pattern_color` is an alias of `shell_pattern_color`: the canonical
+/// gene wins when the genome has it, otherwise the alias value is used,
+/// and either way it appears once, as `shell.pattern_color`.
+pub fn phenotype(
+    genetics: &HashMap<String, GeneValue>,
+    definitions: &GeneDefinitions,
+) -> BTreeMap<&'static str, BTreeMap<String, GeneValue>> {
+    let mut groups: BTreeMap<&'static str, BTreeMap<String, GeneValue>> = BTreeMap::new();
+    
+    for name in definitions.names() {
+        if name == PATTERN_COLOR_ALIAS && definitions.get(PATTERN_COLOR_GENE).is_some() {
+            continue;
+        }
+        
+        let value = match genetics.get(name) {
+            Some(value) => value.clone(),
+            None if name == PATTERN_COLOR_GENE => genetics.get(PATTERN_COLOR_ALIAS)
+                .cloned()
+                .unwrap_or_else(|| definitions.get(name).unwrap().default.clone()),
+            None => definitions.get(name).unwrap().default.clone(),
+        };
+        
+        let (group, key) = PREFIX_GROUPS.iter()
+            .find_map(|(prefix, group)| name.strip_prefix(prefix).map(|key| (*group, key)))
+            .unwrap_or((OTHER_GROUP, name.as_str()));
+        groups.entry(group).or_default().insert(key.to_string(), value);
+    }
+    
+    groups
+}
@@ -0,0 +1,158 @@
+//! Breed presets - named starting genomes for new players
+
+use std::collections::HashMap;
+use crate::types::{GeneValue, Rgb};
+use super::genes::GeneDefinitions;
+use super::validation::{validate, RawGene};
+
+type Genome = HashMap<String, GeneValue>;
+
+/// One trait a breed sets, in the registry's value kinds
+enum Trait {
+    Color(u8, u8, u8),
+    Option(&'static str),
+    Scale(f32),
+}
+
+impl Trait {
+    fn to_raw(&self) -> RawGene {
+        match self {
+            Trait::Color(r, g, b) => RawGene::Rgb(Rgb::new(*r, *g, *b)),
+            Trait::Option(s) => RawGene::Text(s.to_string()),
+            Trait::Scale(f) => RawGene::Number(*f),
+        }
+    }
+    
+    fn to_value(&self) -> GeneValue {
+        match self {
+            Trait::Color(r, g, b) => GeneValue::Rgb(Rgb::new(*r, *g, *b)),
+            Trait::Option(s) => GeneValue::Discrete(s.to_string()),
+            Trait::Scale(f) => GeneValue::Continuous(*f),
+        }
+    }
+}
+
+/// Built-in breeds, sorted by name; each lists the traits it sets on top
+/// of the registry defaults
+const PRESETS: &[(&str, &[(&str, Trait)])] = &[
+    ("albino", &[
+        ("shell_base_color", Trait::Color(245, 240, 230)),
+        ("shell_pattern_color", Trait::Color(255, 225, 225)),
+        ("pattern_color", Trait::Color(255, 225, 225)),
+        ("shell_pattern_opacity", Trait::Scale(0.4)),
+        ("body_base_color", Trait::Color(250, 228, 222)),
+        ("body_pattern_color", Trait::Color(240, 205, 200)),
+        ("head_color", Trait::Color(250, 222, 215)),
+        ("leg_color", Trait::Color(245, 218, 210)),
+        ("eye_color", Trait::Color(220, 40, 60)),
+        ("eye_size_modifier", Trait::Scale(0.9)),
+    ]),
+    ("galapagos", &[
+        ("shell_base_color", Trait::Color(86, 80, 62)),
+        ("shell_pattern_type", Trait::Option("rings")),
+        ("shell_pattern_color", Trait::Color(60, 55, 42)),
+        ("pattern_color", Trait::Color(60, 55, 42)),
+        ("shell_pattern_density", Trait::Scale(0.3)),
+        ("shell_size_modifier", Trait::Scale(1.4)),
+        ("body_base_color", Trait::Color(120, 110, 90)),
+        ("body_pattern_type", Trait::Option("mottled")),
+        ("head_size_modifier", Trait::Scale(1.1)),
+        ("head_color", Trait::Color(112, 102, 82)),
+        ("leg_length", Trait::Scale(0.9)),
+        ("limb_shape", Trait::Option("feet")),
+        ("leg_thickness_modifier", Trait::Scale(1.25)),
+        ("leg_color", Trait::Color(100, 90, 72)),
+    ]),
+    ("painted", &[
+        ("shell_base_color", Trait::Color(40, 50, 36)),
+        ("shell_pattern_type", Trait::Option("stripes")),
+        ("shell_pattern_color", Trait::Color(230, 80, 40)),
+        ("pattern_color", Trait::Color(230, 80, 40)),
+        ("shell_pattern_density", Trait::Scale(0.7)),
+        ("shell_size_modifier", Trait::Scale(0.8)),
+        ("body_base_color", Trait::Color(50, 60, 40)),
+        ("body_pattern_type", Trait::Option("speckled")),
+        ("body_pattern_color", Trait::Color(240, 200, 50)),
+        ("head_color", Trait::Color(60, 70, 45)),
+        ("leg_length", Trait::Scale(1.1)),
+        ("limb_shape", Trait::Option("flippers")),
+    ]),
+];
+
+/// Names of the built-in breeds, sorted
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Full genome for a built-in breed: the registry defaults with the
+/// breed's traits laid over them
+///
+/// Errors if the name is unknown or the breed no longer fits the registry
+/// (a trait gene was removed or its type, range or options changed).
+pub fn preset(name: &str, definitions: &GeneDefinitions) -> Result<Genome, String> {
+    let traits = PRESETS.iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, traits)| *traits)
+        .ok_or_else(|| unknown_preset(name))?;
+    build(name, traits, definitions)
+}
+
+fn unknown_preset(name: &str) -> String {
+    format!("Unknown preset '{}' (expected one of: {})", name, preset_names().join(", "))
+}
+
+/// Validate a breed's traits and lay them over the registry defaults
+fn build(name: &str, traits: &[(&str, Trait)], definitions: &GeneDefinitions) -> Result<Genome, String> {
+    let raw: HashMap<String, RawGene> = traits.iter()
+        .map(|(gene, value)| (gene.to_string(), value.to_raw()))
+        .collect();
+    let report = validate(&raw, definitions);
+    if !report.is_clean() {
+        return Err(format!("Preset '{}' does not fit the registry: {}", name, report.problems().join("; ")));
+    }
+    
+    let mut genetics = definitions.get_defaults();
+    for (gene, value) in traits.iter() {
+        genetics.insert(gene.to_string(), value.to_value());
+    }
+    Ok(genetics)
+}
+
+/// Every built-in breed resolved against one registry
+/// Each breed is validated once here; rebuild the table when the
+/// registry changes
+pub struct Presets {
+    genomes: HashMap<&'static str, Result<Genome, String>>,
+}
+
+impl Presets {
+    pub fn new(definitions: &GeneDefinitions) -> Self {
+        let genomes = PRESETS.iter()
+            .map(|(name, traits)| (*name, build(name, traits, definitions)))
+            .collect();
+        Self { genomes }
+    }
+    
+    /// Genome for a breed, or why it is unavailable
+    pub fn get(&self, name: &str) -> Result<Genome, String> {
+        self.genomes.get(name)
+            .ok_or_else(|| unknown_preset(name))?
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn table_matches_one_off_lookups() {
+        let definitions = GeneDefinitions::new();
+        let presets = Presets::new(&definitions);
+        for name in preset_names() {
+            assert_eq!(presets.get(name), preset(name, &definitions));
+            assert!(presets.get(name).is_ok(), "{}", name);
+        }
+        assert!(presets.get("leatherback").unwrap_err().contains("Unknown preset"));
+    }
+}
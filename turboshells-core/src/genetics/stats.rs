@@ -0,0 +1,219 @@
+//! Stat derivation - maps structural genes to racing stats
+//!
+//! Each stat is a linear function of one structural gene (plus a limb
+//! shape bonus for swim/climb), centred so the default genome yields the
+//! default `TurtleStats`:
+//!
+//! | Stat         | Formula                                              | Clamp    |
+//! |--------------|------------------------------------------------------|----------|
+//! | `speed`      | 5 + 8·(leg_length − 1) − 2·(shell_size_modifier − 1) | 1 – 10   |
+//! | `max_energy` | 100 + 100·(leg_thickness_modifier − 1)               | 50 – 150 |
+//! | `recovery`   | 5 + 10·(head_size_modifier − 1)                      | 1 – 10   |
+//! | `swim`       | 5 + limb swim bonus                                  | 1 – 10   |
+//! | `climb`      | 5 + limb climb bonus + 2·(leg_length − 1)            | 1 – 10   |
+//! | `stamina`    | 3 + 4·(shell_size_modifier − 1)                      | 1 – 10   |
+//! | `luck`       | 3 + 10·(eye_size_modifier − 1)                       | 1 – 10   |
+//! | `size`       | shell_size_modifier                                  | 0.5 – 1.5 |
+//!
+//! Limb bonuses (swim, climb): flippers (0, 0), fins (+3, −2), feet (−1, +3).
+
+use std::collections::HashMap;
+use rand::Rng;
+use crate::types::{GeneValue, TurtleStats};
+use super::genes::GeneDefinitions;
+
+const SPEED_PER_LEG: f32 = 8.0;
+const SPEED_PER_SHELL: f32 = 2.0;
+const ENERGY_PER_THICKNESS: f32 = 100.0;
+const RECOVERY_PER_HEAD: f32 = 10.0;
+const CLIMB_PER_LEG: f32 = 2.0;
+const STAMINA_PER_SHELL: f32 = 4.0;
+const LUCK_PER_EYE: f32 = 10.0;
+
+/// (swim, climb) bonus for each limb shape
+fn limb_bonus(limb_shape: &str) -> (f32, f32) {
+    match limb_shape {
+        "fins" => (3.0, -2.0),
+        "feet" => (-1.0, 3.0),
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Read a continuous gene, falling back to the registry default
+fn continuous(genetics: &HashMap<String, GeneValue>, definitions: &GeneDefinitions, name: &str) -> f32 {
+    genetics.get(name)
+        .or_else(|| definitions.get(name).map(|d| &d.default))
+        .and_then(|v| v.as_continuous())
+        .unwrap_or(1.0)
+}
+
+/// Derive racing stats from genetics using the documented mapping
+pub fn derive_stats(genetics: &HashMap<String, GeneValue>, definitions: &GeneDefinitions) -> TurtleStats {
+    let leg = continuous(genetics, definitions, "leg_length");
+    let thickness = continuous(genetics, definitions, "leg_thickness_modifier");
+    let shell = continuous(genetics, definitions, "shell_size_modifier");
+    let head = continuous(genetics, definitions, "head_size_modifier");
+    let eye = continuous(genetics, definitions, "eye_size_modifier");
+    let limb = genetics.get("limb_shape")
+        .and_then(|v| v.as_discrete())
+        .unwrap_or("flippers");
+    let (swim_bonus, climb_bonus) = limb_bonus(limb);
+    
+    TurtleStats {
+        speed: (5.0 + SPEED_PER_LEG * (leg - 1.0) - SPEED_PER_SHELL * (shell - 1.0)).clamp(1.0, 10.0),
+        max_energy: (100.0 + ENERGY_PER_THICKNESS * (thickness - 1.0)).clamp(50.0, 150.0),
+        recovery: (5.0 + RECOVERY_PER_HEAD * (head - 1.0)).clamp(1.0, 10.0),
+        swim: (5.0 + swim_bonus).clamp(1.0, 10.0),
+        climb: (5.0 + climb_bonus + CLIMB_PER_LEG * (leg - 1.0)).clamp(1.0, 10.0),
+        stamina: (3.0 + STAMINA_PER_SHELL * (shell - 1.0)).clamp(1.0, 10.0),
+        luck: (3.0 + LUCK_PER_EYE * (eye - 1.0)).clamp(1.0, 10.0),
+        size: shell.clamp(0.5, 1.5),
+        ..TurtleStats::default()
+    }
+}
+
+/// Derived stats as named pairs, the keys `PyGenetics::derive_stats`
+/// returns; `max_energy` is keyed "energy" to match `PyTurtle`
+pub fn stat_targets(stats: &TurtleStats) -> Vec<(&'static str, f32)> {
+    vec![
+        ("speed", stats.speed),
+        ("energy", stats.max_energy),
+        ("recovery", stats.recovery),
+        ("swim", stats.swim),
+        ("climb", stats.climb),
+        ("stamina", stats.stamina),
+        ("luck", stats.luck),
+        ("size", stats.size),
+        ("grit", stats.grit),
+    ]
+}
+
+/// Build a best-fit genome for (a subset of) target stats
+///
+/// Inverts the mapping for the structural genes, clamping each to its
+/// registered range; stats absent from `targets` leave their gene random.
+/// `limb_shape` is chosen to minimise swim/climb error. All other genes
+/// are random. The mapping is many-to-one and clamped, so unreachable
+/// targets yield the closest achievable genome rather than an exact one.
+/// Keys follow `stat_targets`, so `derive_stats` output feeds straight
+/// back in; "max_energy" is accepted as an alias of "energy".
+pub fn genome_for_stats<R: Rng + ?Sized>(
+    targets: &HashMap<String, f32>,
+    definitions: &GeneDefinitions,
+    rng: &mut R,
+) -> HashMap<String, GeneValue> {
+    let mut genetics = definitions.generate_random_with(rng);
+    let mut set = |name: &str, value: f32| {
+        let value = match definitions.get(name).and_then(|d| d.continuous_range) {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        };
+        genetics.insert(name.to_string(), GeneValue::Continuous(value));
+    };
+    
+    if let Some(stamina) = targets.get("stamina") {
+        set("shell_size_modifier", 1.0 + (stamina - 3.0) / STAMINA_PER_SHELL);
+    }
+    if let Some(max_energy) = targets.get("energy").or_else(|| targets.get("max_energy")) {
+        set("leg_thickness_modifier", 1.0 + (max_energy - 100.0) / ENERGY_PER_THICKNESS);
+    }
+    if let Some(recovery) = targets.get("recovery") {
+        set("head_size_modifier", 1.0 + (recovery - 5.0) / RECOVERY_PER_HEAD);
+    }
+    if let Some(luck) = targets.get("luck") {
+        set("eye_size_modifier", 1.0 + (luck - 3.0) / LUCK_PER_EYE);
+    }
+    
+    let shell = continuous(&genetics, definitions, "shell_size_modifier");
+    if let Some(speed) = targets.get("speed") {
+        let leg = 1.0 + (speed - 5.0 + SPEED_PER_SHELL * (shell - 1.0)) / SPEED_PER_LEG;
+        let range = definitions.get("leg_length").and_then(|d| d.continuous_range);
+        let leg = range.map_or(leg, |(min, max)| leg.clamp(min, max));
+        genetics.insert("leg_length".to_string(), GeneValue::Continuous(leg));
+    }
+    
+    let swim = targets.get("swim");
+    let climb = targets.get("climb");
+    if swim.is_some() || climb.is_some() {
+        let leg = continuous(&genetics, definitions, "leg_length");
+        let options = definitions.get("limb_shape")
+            .and_then(|d| d.discrete_options.clone())
+            .unwrap_or_default();
+        let error = |limb: &str| {
+            let (swim_bonus, climb_bonus) = limb_bonus(limb);
+            let swim_err = swim.map_or(0.0, |s| (5.0 + swim_bonus - s).abs());
+            let climb_err = climb.map_or(0.0, |c| (5.0 + climb_bonus + CLIMB_PER_LEG * (leg - 1.0) - c).abs());
+            swim_err + climb_err
+        };
+        if let Some(best) = options.iter().min_by(|a, b| error(a).total_cmp(&error(b))) {
+            genetics.insert("limb_shape".to_string(), GeneValue::Discrete(best.clone()));
+        }
+    }
+    
+    genetics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    
+    #[test]
+    fn genome_for_stats_round_trips_achievable_targets() {
+        let definitions = GeneDefinitions::new();
+        let targets: HashMap<String, f32> = [
+            ("speed", 6.0),
+            ("max_energy", 110.0),
+            ("recovery", 6.0),
+            ("swim", 8.0),
+            ("climb", 3.4),
+            ("stamina", 4.0),
+            ("luck", 4.0),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+        
+        for seed in 0..5 {
+            let genome = genome_for_stats(&targets, &definitions, &mut rng::seeded(seed));
+            let stats = derive_stats(&genome, &definitions);
+            for (name, target) in &targets {
+                let got = stats.get(name).unwrap();
+                assert!((got - target).abs() < 0.05, "{}: {} vs {}", name, got, target);
+            }
+        }
+    }
+    
+    #[test]
+    fn genome_for_stats_clamps_unreachable_targets() {
+        let definitions = GeneDefinitions::new();
+        let targets = HashMap::from([("speed".to_string(), 50.0)]);
+        let genome = genome_for_stats(&targets, &definitions, &mut rng::seeded(1));
+        assert_eq!(genome["leg_length"], GeneValue::Continuous(1.5));
+    }
+    
+    #[test]
+    fn derive_stats_output_feeds_back_into_genome_for_stats() {
+        let definitions = GeneDefinitions::new();
+        let mut source = definitions.get_defaults();
+        source.insert("leg_thickness_modifier".to_string(), GeneValue::Continuous(1.3));
+        source.insert("leg_length".to_string(), GeneValue::Continuous(1.1));
+        let expected = derive_stats(&source, &definitions);
+        
+        let targets: HashMap<String, f32> = stat_targets(&expected)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        let stats = derive_stats(&genome_for_stats(&targets, &definitions, &mut rng::seeded(2)), &definitions);
+        assert!((stats.max_energy - expected.max_energy).abs() < 0.5, "{} vs {}", stats.max_energy, expected.max_energy);
+        assert!((stats.speed - expected.speed).abs() < 0.05);
+    }
+    
+    #[test]
+    fn genome_for_stats_survives_nan_targets() {
+        let definitions = GeneDefinitions::new();
+        let targets = HashMap::from([("swim".to_string(), f32::NAN), ("climb".to_string(), 5.0)]);
+        let genome = genome_for_stats(&targets, &definitions, &mut rng::seeded(1));
+        assert!(matches!(genome["limb_shape"], GeneValue::Discrete(_)));
+    }
+}
@@ -0,0 +1,80 @@
+//! Genome tweening - cheap per-frame interpolation between two looks
+
+use std::collections::HashMap;
+use crate::types::GeneValue;
+use super::genes::GeneDefinitions;
+
+/// Pre-parsed pair of genomes that can be sampled at any `t`
+///
+/// Colors blend channel-wise and continuous genes interpolate linearly;
+/// discrete genes cannot blend, so they switch from `a` to `b` at t = 0.5.
+#[derive(Clone, Debug)]
+pub struct Tween {
+    genes: Vec<(String, GeneValue, GeneValue)>,
+}
+
+impl Tween {
+    /// Pair up every registered gene, defaulting any missing from a genome
+    pub fn new(
+        a: &HashMap<String, GeneValue>,
+        b: &HashMap<String, GeneValue>,
+        definitions: &GeneDefinitions,
+    ) -> Self {
+        let genes = definitions.names()
+            .into_iter()
+            .map(|name| {
+                let default = &definitions.get(name).unwrap().default;
+                let start = a.get(name).unwrap_or(default).clone();
+                let end = b.get(name).unwrap_or(default).clone();
+                (name.clone(), start, end)
+            })
+            .collect();
+        Self { genes }
+    }
+    
+    /// Genome at `t` (clamped to [0, 1]), in sorted gene order
+    pub fn at(&self, t: f32) -> Vec<(&String, GeneValue)> {
+        let t = t.clamp(0.0, 1.0);
+        self.genes
+            .iter()
+            .map(|(name, start, end)| {
+                let value = match (start, end) {
+                    (GeneValue::Rgb(c1), GeneValue::Rgb(c2)) => GeneValue::Rgb(c1.blend(c2, t)),
+                    (GeneValue::Continuous(f1), GeneValue::Continuous(f2)) => {
+                        GeneValue::Continuous(f1 + (f2 - f1) * t)
+                    },
+                    _ => if t < 0.5 { start.clone() } else { end.clone() },
+                };
+                (name, value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    
+    #[test]
+    fn endpoints_match_and_continuous_genes_meet_halfway() {
+        let definitions = GeneDefinitions::new();
+        let mut rng = rng::seeded(6);
+        let a = definitions.generate_random_with(&mut rng);
+        let b = definitions.generate_random_with(&mut rng);
+        let tween = Tween::new(&a, &b, &definitions);
+        
+        let at = |t: f32| -> HashMap<String, GeneValue> {
+            tween.at(t).into_iter().map(|(name, value)| (name.clone(), value)).collect()
+        };
+        assert_eq!(at(0.0), a);
+        assert_eq!(at(1.0), b);
+        for (name, value) in at(0.5) {
+            if let GeneValue::Continuous(mid) = value {
+                let start = a[&name].as_continuous().unwrap();
+                let end = b[&name].as_continuous().unwrap();
+                assert!((mid - (start + end) / 2.0).abs() < 1e-6, "{}", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Validation of externally supplied genetics against the registry
+
+use std::collections::HashMap;
+use rayon::prelude::*;
+use crate::types::Rgb;
+use super::genes::GeneDefinitions;
+
+/// A gene value as read from outside, before it is checked against the registry
+#[derive(Clone, Debug)]
+pub enum RawGene {
+    Rgb(Rgb),
+    Text(String),
+    Number(f32),
+    /// Any other value, described by its type name
+    Other(String),
+}
+
+impl RawGene {
+    /// Name of the kind of value this is, for error messages
+    pub fn kind(&self) -> &str {
+        match self {
+            RawGene::Rgb(_) => "rgb",
+            RawGene::Text(_) => "str",
+            RawGene::Number(_) => "float",
+            RawGene::Other(type_name) => type_name,
+        }
+    }
+}
+
+/// Everything wrong with a genetics dict; empty when it is clean
+#[derive(Clone, Debug, Default)]
+pub struct ValidationReport {
+    pub unknown_keys: Vec<String>,
+    /// (gene, expected type, actual type)
+    pub type_errors: Vec<(String, String, String)>,
+    /// (gene, value, allowed range)
+    pub out_of_range: Vec<(String, f32, (f32, f32))>,
+    /// (gene, value) for discrete values not among the gene's options
+    pub invalid_options: Vec<(String, String)>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.unknown_keys.is_empty()
+            && self.type_errors.is_empty()
+            && self.out_of_range.is_empty()
+            && self.invalid_options.is_empty()
+    }
+    
+    /// Human-readable list of every problem found
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for name in &self.unknown_keys {
+            problems.push(format!("unknown gene '{}'", name));
+        }
+        for (name, expected, got) in &self.type_errors {
+            problems.push(format!("'{}': expected {}, got {}", name, expected, got));
+        }
+        for (name, value, (min, max)) in &self.out_of_range {
+            problems.push(format!("'{}': {} outside range [{}, {}]", name, value, min, max));
+        }
+        for (name, value) in &self.invalid_options {
+            problems.push(format!("'{}': '{}' is not a valid option", name, value));
+        }
+        problems
+    }
+}
+
+/// Check raw genetics against the registry without modifying anything
+pub fn validate(genetics: &HashMap<String, RawGene>, definitions: &GeneDefinitions) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let mut names: Vec<&String> = genetics.keys().collect();
+    names.sort();
+    
+    for name in names {
+        let value = &genetics[name];
+        let def = match definitions.get(name) {
+            Some(def) => def,
+            None => {
+                report.unknown_keys.push(name.clone());
+                continue;
+            },
+        };
+        
+        match (def.gene_type.as_str(), value) {
+            ("rgb", RawGene::Rgb(_)) => {},
+            ("discrete", RawGene::Text(s)) => {
+                let valid = def.discrete_options.as_ref().is_none_or(|options| options.contains(s));
+                if !valid {
+                    report.invalid_options.push((name.clone(), s.clone()));
+                }
+            },
+            ("continuous", RawGene::Number(f)) => {
+                if let Some((min, max)) = def.continuous_range {
+                    if !(min..=max).contains(f) {
+                        report.out_of_range.push((name.clone(), *f, (min, max)));
+                    }
+                }
+            },
+            (expected, _) => {
+                report.type_errors.push((name.clone(), expected.to_string(), value.kind().to_string()));
+            },
+        }
+    }
+    
+    report
+}
+
+/// Problem list for each genome, in input order (empty = clean)
+/// Genomes are checked in parallel
+pub fn validate_batch(genomes: &[HashMap<String, RawGene>], definitions: &GeneDefinitions) -> Vec<Vec<String>> {
+    genomes.par_iter()
+        .map(|g| validate(g, definitions).problems())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn validate_batch_flags_the_bad_rows() {
+        let definitions = GeneDefinitions::new();
+        let clean = HashMap::from([
+            ("leg_length".to_string(), RawGene::Number(1.1)),
+            ("limb_shape".to_string(), RawGene::Text("fins".to_string())),
+        ]);
+        let out_of_range = HashMap::from([("leg_length".to_string(), RawGene::Number(9.0))]);
+        let wrong_type = HashMap::from([("shell_base_color".to_string(), RawGene::Text("green".to_string()))]);
+        let unknown = HashMap::from([("wings".to_string(), RawGene::Number(1.0))]);
+        
+        let report = validate_batch(
+            &[clean.clone(), out_of_range, clean, wrong_type, unknown],
+            &definitions,
+        );
+        let bad: Vec<usize> = report.iter()
+            .enumerate()
+            .filter(|(_, problems)| !problems.is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(bad, vec![1, 3, 4]);
+        assert_eq!(report[4], vec!["unknown gene 'wings'".to_string()]);
+    }
+}
@@ -6,11 +6,12 @@
 use pyo3::prelude::*;
 
 pub mod genetics;
+pub mod rng;
 pub mod simulation;
 pub mod types;
 
-use genetics::PyGenetics;
-use simulation::{PyTurtle, PyRace};
+use genetics::{GenomeTweener, PyGenetics};
+use simulation::{run_tournament_py, set_turtle_id_seed, PyTurtle, PyRace};
 
 /// TurboShells Core Python Module
 /// 
@@ -20,11 +21,15 @@ use simulation::{PyTurtle, PyRace};
 #[pymodule]
 fn turboshells_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyGenetics>()?;
+    m.add_class::<GenomeTweener>()?;
     m.add_class::<PyTurtle>()?;
     m.add_class::<PyRace>()?;
+    m.add_function(wrap_pyfunction!(run_tournament_py, m)?)?;
+    m.add_function(wrap_pyfunction!(set_turtle_id_seed, m)?)?;
     
     // Version info
     m.add("__version__", "0.1.0")?;
+    m.add("RNG_ALGORITHM", rng::RNG_ALGORITHM)?;
     
     Ok(())
 }
@@ -0,0 +1,66 @@
+//! Seeded random number generation helpers
+//!
+//! Every reproducible code path draws from a `SeededRng` built here, so
+//! a single seed fully determines tracks, races and derived sub-streams.
+//! The algorithm is pinned (unlike `StdRng`, which rand may change between
+//! releases), so saved seeds reproduce across machines and crate updates.
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// RNG used by all seeded code paths
+pub type SeededRng = ChaCha8Rng;
+
+/// Name of the seeded RNG algorithm, stored alongside saved seeds
+pub const RNG_ALGORITHM: &str = "ChaCha8";
+
+/// Base seed for streams that have no race or caller seed to derive from
+pub const DEFAULT_SEED: u64 = 0x7475_7274_6C65;
+
+/// Create an RNG from a fixed seed
+pub fn seeded(seed: u64) -> SeededRng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+/// Draw a fresh seed from the thread RNG
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+/// Derive an independent sub-seed from a base seed and an index
+///
+/// Uses the SplitMix64 finalizer so neighbouring indices produce
+/// uncorrelated streams.
+pub fn derive_seed(base: u64, index: u64) -> u64 {
+    let mut z = base.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Pinned outputs: if a dependency bump changes these, saved seeds
+    /// no longer reproduce and the change needs a new `RNG_ALGORITHM`
+    #[test]
+    fn seeded_stream_matches_pinned_chacha8_outputs() {
+        let mut rng = seeded(42);
+        let outputs: Vec<u64> = (0..4).map(|_| rng.gen()).collect();
+        assert_eq!(outputs, [
+            0xae90_bfb5_395d_5ba1,
+            0xf345_3fc6_2579_9188,
+            0x6d71_b708_c5b6_538c,
+            0xa09a_b2f9_5816_6752,
+        ]);
+        assert_eq!(RNG_ALGORITHM, "ChaCha8");
+    }
+    
+    #[test]
+    fn derive_seed_matches_splitmix64() {
+        // First two SplitMix64 outputs for state 42
+        assert_eq!(derive_seed(42, 0), 0xbdd7_3226_2feb_6e95);
+        assert_eq!(derive_seed(42, 1), 0x28ef_e333_b266_f103);
+    }
+}
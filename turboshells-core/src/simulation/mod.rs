@@ -5,13 +5,18 @@
 mod turtle;
 mod terrain;
 mod race;
+mod tournament;
 
-pub use turtle::Turtle;
-pub use terrain::{Terrain, TerrainType};
-pub use race::Race;
+pub use turtle::{normalize_id, set_id_seed, size_factor, terrain_multiplier, LuckMode, LuckRoll, PhysicsConfig, SpeedBreakdown, StatModifier, StepOutcome, TimedModifier, Turtle, ID_LEN, PROJECTION_WINDOW};
+pub use terrain::{Terrain, TerrainType, Weather};
+pub use tournament::{round_robin, run_tournament, Bracket, Match, Standing};
+pub use race::{validate_length, Excitement, FinishMode, LuckEvent, Placement, Projection, Race, StageResult, StageStanding, WinReason, DEFAULT_MAX_TICKS, DEFAULT_SEGMENT_SIZE};
 
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::types::{PyBytes, PyDict, PyList};
+use std::collections::HashMap;
+use crate::rng;
 use crate::types::TurtleStats;
 
 /// Python-exposed Turtle class
@@ -23,7 +28,8 @@ pub struct PyTurtle {
 #[pymethods]
 impl PyTurtle {
     #[new]
-    #[pyo3(signature = (name, speed, energy, recovery, swim, climb, stamina=3.0, luck=3.0))]
+    #[pyo3(signature = (name, speed, energy, recovery, swim, climb, stamina=3.0, luck=3.0, size=1.0, grit=5.0))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: String,
         speed: f32,
@@ -33,6 +39,8 @@ impl PyTurtle {
         climb: f32,
         stamina: f32,
         luck: f32,
+        size: f32,
+        grit: f32,
     ) -> Self {
         Self {
             inner: Turtle::new(
@@ -45,11 +53,54 @@ impl PyTurtle {
                     climb,
                     stamina,
                     luck,
+                    size,
+                    grit,
                 },
             ),
         }
     }
     
+    /// Create a turtle with a fixed id instead of a generated one
+    ///
+    /// The id is fit to 8 characters: longer ids are truncated, shorter
+    /// ones right-padded with '0'. Empty ids are rejected.
+    #[staticmethod]
+    #[pyo3(signature = (name, id, speed, energy, recovery, swim, climb, stamina=3.0, luck=3.0, size=1.0, grit=5.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_id(
+        name: String,
+        id: &str,
+        speed: f32,
+        energy: f32,
+        recovery: f32,
+        swim: f32,
+        climb: f32,
+        stamina: f32,
+        luck: f32,
+        size: f32,
+        grit: f32,
+    ) -> PyResult<Self> {
+        let id = normalize_id(id)
+            .ok_or_else(|| PyValueError::new_err("Turtle id must not be empty"))?;
+        Ok(Self {
+            inner: Turtle::with_id(
+                name,
+                id,
+                TurtleStats {
+                    speed,
+                    max_energy: energy,
+                    recovery,
+                    swim,
+                    climb,
+                    stamina,
+                    luck,
+                    size,
+                    grit,
+                },
+            ),
+        })
+    }
+    
     #[getter]
     pub fn name(&self) -> &str {
         &self.inner.name
@@ -80,45 +131,500 @@ impl PyTurtle {
         self.inner.finished
     }
     
+    /// Accumulated fatigue this race; speed is scaled by `1 / (1 + fatigue)`
+    #[getter]
+    pub fn fatigue(&self) -> f32 {
+        self.inner.fatigue
+    }
+    
+    /// Tenacity (0-10); sets the energy fraction at which resting ends
+    #[getter]
+    pub fn grit(&self) -> f32 {
+        self.inner.stats.grit
+    }
+    
+    #[setter]
+    pub fn set_grit(&mut self, grit: f32) -> PyResult<()> {
+        if !(0.0..=10.0).contains(&grit) {
+            return Err(PyValueError::new_err("grit must be between 0 and 10"));
+        }
+        self.inner.stats.grit = grit;
+        Ok(())
+    }
+    
+    /// Live race state in one call: `id`, `name`, `current_energy`,
+    /// `race_distance`, `is_resting`, `finished` and `fatigue`
+    pub fn snapshot<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        turtle_snapshot(py, &self.inner)
+    }
+    
     /// Reset turtle for a new race
     pub fn reset_for_race(&mut self) {
         self.inner.reset_for_race();
     }
     
     /// Update physics for one tick
-    /// Returns distance moved
-    pub fn update_physics(&mut self, terrain_type: &str, speed_mod: f32, energy_drain: f32) -> f32 {
-        let terrain = Terrain::from_str(terrain_type, speed_mod, energy_drain);
+    /// Returns distance moved; `grade` only applies to "incline"
+    #[pyo3(signature = (terrain_type, speed_mod, energy_drain, grade=0.0))]
+    pub fn update_physics(&mut self, terrain_type: &str, speed_mod: f32, energy_drain: f32, grade: f32) -> f32 {
+        let terrain = Terrain::from_str(terrain_type, speed_mod, energy_drain).with_grade(grade);
         self.inner.update_physics(&terrain)
     }
     
-    /// Get stats as dict
-    pub fn get_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+    /// Explain the move speed on a terrain without advancing physics
+    /// Returns base speed, terrain multiplier, energy/fatigue/pacing/luck
+    /// factors, the target speed, the carried speed and inertia blended
+    /// into it, and final speed
+    #[pyo3(signature = (terrain_type, speed_mod, energy_drain, grade=0.0))]
+    pub fn speed_breakdown<'py>(&self, py: Python<'py>, terrain_type: &str, speed_mod: f32, energy_drain: f32, grade: f32) -> PyResult<Bound<'py, PyDict>> {
+        let terrain = Terrain::from_str(terrain_type, speed_mod, energy_drain).with_grade(grade);
+        let breakdown = self.inner.speed_breakdown(&terrain);
+        let dict = PyDict::new(py);
+        dict.set_item("base_speed", breakdown.base_speed)?;
+        dict.set_item("terrain_multiplier", breakdown.terrain_multiplier)?;
+        dict.set_item("size_factor", breakdown.size_factor)?;
+        dict.set_item("energy_factor", breakdown.energy_factor)?;
+        dict.set_item("fatigue_factor", breakdown.fatigue_factor)?;
+        dict.set_item("pacing_factor", breakdown.pacing_factor)?;
+        dict.set_item("luck_factor", breakdown.luck_factor)?;
+        dict.set_item("target_speed", breakdown.target_speed)?;
+        dict.set_item("carried_speed", breakdown.carried_speed)?;
+        dict.set_item("inertia", breakdown.inertia)?;
+        dict.set_item("final_speed", breakdown.final_speed)?;
+        Ok(dict)
+    }
+    
+    /// Update physics tuning parameters; omitted values are unchanged
+    ///
+    /// `recovery_threshold` is the energy fraction a resting turtle waits
+    /// for (before grit scaling) and must be in (0, 1]; a higher one rests
+    /// longer but resumes fuller. `mud_energy_exponent` (positive, 1.0 =
+    /// linear) shapes how mud speed falls with energy: below 1 helps tired
+    /// turtles, above 1 punishes them. `inertia` in [0, 1) is the share
+    /// of last tick's speed carried into the next, smoothing terrain
+    /// changes; resting drops carried speed to zero. `base_energy_drain`
+    /// (non-negative) is the energy cost of a move before terrain; lower
+    /// it to stretch how far a turtle runs between rests. `recovery_rate`
+    /// and `terrain_difficulty` must be non-negative. These survive
+    /// `reset_for_race`.
+    #[pyo3(signature = (*, resting_drift=None, size_effects=None, recovery_rate=None, recovery_threshold=None, terrain_difficulty=None, mud_energy_exponent=None, inertia=None, base_energy_drain=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_physics_params(
+        &mut self,
+        resting_drift: Option<f32>,
+        size_effects: Option<bool>,
+        recovery_rate: Option<f32>,
+        recovery_threshold: Option<f32>,
+        terrain_difficulty: Option<f32>,
+        mud_energy_exponent: Option<f32>,
+        inertia: Option<f32>,
+        base_energy_drain: Option<f32>,
+    ) -> PyResult<()> {
+        for (name, value) in [("recovery_rate", recovery_rate), ("terrain_difficulty", terrain_difficulty)] {
+            if let Some(value) = value {
+                if value < 0.0 || !value.is_finite() {
+                    return Err(PyValueError::new_err(format!("{} must be non-negative, got {}", name, value)));
+                }
+            }
+        }
+        if let Some(threshold) = recovery_threshold {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return Err(PyValueError::new_err(format!("recovery_threshold must be in (0, 1], got {}", threshold)));
+            }
+        }
+        if let Some(exponent) = mud_energy_exponent {
+            if exponent <= 0.0 || !exponent.is_finite() {
+                return Err(PyValueError::new_err(format!("mud_energy_exponent must be positive, got {}", exponent)));
+            }
+        }
+        if let Some(inertia) = inertia {
+            if !(0.0..1.0).contains(&inertia) {
+                return Err(PyValueError::new_err(format!("inertia must be in [0, 1), got {}", inertia)));
+            }
+        }
+        if let Some(drain) = base_energy_drain {
+            if drain < 0.0 || !drain.is_finite() {
+                return Err(PyValueError::new_err(format!("base_energy_drain must be non-negative, got {}", drain)));
+            }
+        }
+        
+        if let Some(threshold) = recovery_threshold {
+            self.inner.physics.recovery_threshold = threshold;
+        }
+        if let Some(exponent) = mud_energy_exponent {
+            self.inner.physics.mud_energy_exponent = exponent;
+        }
+        if let Some(inertia) = inertia {
+            self.inner.physics.inertia = inertia;
+        }
+        if let Some(drain) = base_energy_drain {
+            self.inner.physics.base_energy_drain = drain;
+        }
+        if let Some(rate) = recovery_rate {
+            self.inner.physics.recovery_rate = rate;
+        }
+        if let Some(difficulty) = terrain_difficulty {
+            self.inner.physics.terrain_difficulty = difficulty;
+        }
+        if let Some(drift) = resting_drift {
+            self.inner.physics.resting_drift = drift;
+        }
+        if let Some(enabled) = size_effects {
+            self.inner.physics.size_effects = enabled;
+        }
+        Ok(())
+    }
+    
+    /// Get physics tuning parameters as dict
+    pub fn get_physics_params<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
         let dict = PyDict::new(py);
-        dict.set_item("speed", self.inner.stats.speed)?;
-        dict.set_item("max_energy", self.inner.stats.max_energy)?;
-        dict.set_item("recovery", self.inner.stats.recovery)?;
-        dict.set_item("swim", self.inner.stats.swim)?;
-        dict.set_item("climb", self.inner.stats.climb)?;
-        dict.set_item("stamina", self.inner.stats.stamina)?;
-        dict.set_item("luck", self.inner.stats.luck)?;
+        dict.set_item("resting_drift", self.inner.physics.resting_drift)?;
+        dict.set_item("size_effects", self.inner.physics.size_effects)?;
+        dict.set_item("recovery_rate", self.inner.physics.recovery_rate)?;
+        dict.set_item("recovery_threshold", self.inner.physics.recovery_threshold)?;
+        dict.set_item("terrain_difficulty", self.inner.physics.terrain_difficulty)?;
+        dict.set_item("mud_energy_exponent", self.inner.physics.mud_energy_exponent)?;
+        dict.set_item("inertia", self.inner.physics.inertia)?;
+        dict.set_item("base_energy_drain", self.inner.physics.base_energy_drain)?;
         Ok(dict)
     }
+    
+    /// Equip an item modifying one stat: `(base + add) * mult`
+    #[pyo3(signature = (stat, add=0.0, mult=1.0))]
+    pub fn add_equipment(&mut self, stat: &str, add: f32, mult: f32) -> PyResult<()> {
+        if self.inner.stats.get(stat).is_none() {
+            return Err(PyKeyError::new_err(format!("Unknown stat '{}'", stat)));
+        }
+        self.inner.equipment.push(StatModifier { stat: stat.to_string(), add, mult });
+        Ok(())
+    }
+    
+    /// Remove all equipment
+    pub fn clear_equipment(&mut self) {
+        self.inner.equipment.clear();
+    }
+    
+    /// Add `delta` to a stat for the next `duration_ticks` physics ticks
+    ///
+    /// Buffs (positive) and debuffs (negative) stack on top of equipment
+    /// and count down each tick, resting or not, then expire;
+    /// `reset_for_race` clears them. `get_effective_stats` includes them,
+    /// `get_stats` does not.
+    pub fn apply_modifier(&mut self, stat: &str, delta: f32, duration_ticks: u32) -> PyResult<()> {
+        if self.inner.stats.get(stat).is_none() {
+            return Err(PyKeyError::new_err(format!("Unknown stat '{}'", stat)));
+        }
+        if duration_ticks == 0 {
+            return Err(PyValueError::new_err("duration_ticks must be at least 1"));
+        }
+        self.inner.modifiers.push(TimedModifier { stat: stat.to_string(), delta, ticks_left: duration_ticks });
+        Ok(())
+    }
+    
+    /// Active timed modifiers as (stat, delta, ticks_left)
+    pub fn get_modifiers(&self) -> Vec<(String, f32, u32)> {
+        self.inner.modifiers
+            .iter()
+            .map(|m| (m.stat.clone(), m.delta, m.ticks_left))
+            .collect()
+    }
+    
+    /// Whether equipment survives the end of a race
+    #[getter]
+    pub fn keep_equipment(&self) -> bool {
+        self.inner.keep_equipment
+    }
+    
+    #[setter]
+    pub fn set_keep_equipment(&mut self, keep: bool) {
+        self.inner.keep_equipment = keep;
+    }
+    
+    /// Single 0-100 power score from base stats (see `TurtleStats::rating`)
+    pub fn overall_rating(&self) -> f32 {
+        self.inner.stats.rating()
+    }
+    
+    /// Whether the turtle eases off at low energy instead of running dry
+    /// See `Turtle::pacing_effort` for the stamina-scaled threshold and effort
+    #[getter]
+    pub fn pacing(&self) -> bool {
+        self.inner.pacing_enabled
+    }
+    
+    /// Turn energy-aware pacing on or off
+    pub fn set_pacing(&mut self, enabled: bool) {
+        self.inner.pacing_enabled = enabled;
+    }
+    
+    /// Get base stats as dict
+    pub fn get_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        stats_to_pydict(py, &self.inner.stats)
+    }
+    
+    /// Get stats with equipment and timed modifiers applied as dict
+    pub fn get_effective_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        stats_to_pydict(py, &self.inner.effective_stats())
+    }
+}
+
+/// One standing, as returned by `get_results`
+fn placement_to_pydict(py: Python<'_>, placement: Placement) -> PyResult<Bound<'_, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", placement.name)?;
+    dict.set_item("place", placement.place)?;
+    dict.set_item("finish_tick", placement.finish_tick)?;
+    dict.set_item("finish_time", placement.finish_time)?;
+    dict.set_item("distance", placement.distance)?;
+    Ok(dict)
+}
+
+/// Per-frame state of a turtle, as returned by `snapshot`
+fn turtle_snapshot<'py>(py: Python<'py>, turtle: &Turtle) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("id", &turtle.id)?;
+    dict.set_item("name", &turtle.name)?;
+    dict.set_item("current_energy", turtle.current_energy)?;
+    dict.set_item("race_distance", turtle.race_distance)?;
+    dict.set_item("is_resting", turtle.is_resting)?;
+    dict.set_item("finished", turtle.finished)?;
+    dict.set_item("fatigue", turtle.fatigue)?;
+    Ok(dict)
+}
+
+fn stats_to_pydict<'py>(py: Python<'py>, stats: &TurtleStats) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("speed", stats.speed)?;
+    dict.set_item("max_energy", stats.max_energy)?;
+    dict.set_item("recovery", stats.recovery)?;
+    dict.set_item("swim", stats.swim)?;
+    dict.set_item("climb", stats.climb)?;
+    dict.set_item("stamina", stats.stamina)?;
+    dict.set_item("luck", stats.luck)?;
+    dict.set_item("size", stats.size)?;
+    dict.set_item("grit", stats.grit)?;
+    Ok(dict)
+}
+
+/// A hand-authored track segment, with or without an incline grade
+#[derive(FromPyObject)]
+pub enum SegmentSpec {
+    Graded(String, f32, f32, f32),
+    Flat(String, f32, f32),
 }
 
 /// Python-exposed Race class
 #[pyclass]
 pub struct PyRace {
     inner: Race,
+    step_hook: Option<PyObject>,
 }
 
 #[pymethods]
 impl PyRace {
+    /// Create a race; `finish_mode` is "all" (run until every turtle
+    /// finishes) or "first" (stop when the leader crosses the line).
+    /// `segment_size` sets the terrain granularity, `max_ticks` the tick cap
     #[new]
-    pub fn new(track_length: f32) -> Self {
-        Self {
-            inner: Race::new(track_length),
+    #[pyo3(signature = (track_length, finish_mode="all", segment_size=DEFAULT_SEGMENT_SIZE, max_ticks=DEFAULT_MAX_TICKS))]
+    pub fn new(track_length: f32, finish_mode: &str, segment_size: f32, max_ticks: u32) -> PyResult<Self> {
+        Self::new_seeded(track_length, rng::random_seed(), finish_mode, segment_size, max_ticks)
+    }
+    
+    /// Create a race whose track and luck rolls derive from `seed`
+    /// The same seed, roster and settings always produce the same race
+    #[staticmethod]
+    #[pyo3(signature = (track_length, seed, finish_mode="all", segment_size=DEFAULT_SEGMENT_SIZE, max_ticks=DEFAULT_MAX_TICKS))]
+    pub fn new_seeded(track_length: f32, seed: u64, finish_mode: &str, segment_size: f32, max_ticks: u32) -> PyResult<Self> {
+        check_track_length(track_length)?;
+        check_segment_size(segment_size)?;
+        let mut inner = Race::with_params(track_length, seed, segment_size, max_ticks);
+        inner.finish_mode = parse_finish_mode(finish_mode)?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Create a race whose terrain mix follows `weights`
+    ///
+    /// `weights` maps terrain names ("normal", "water", "rocks", "sand",
+    /// "mud", "boost", "ice", "incline") to relative weights; missing types
+    /// never appear. Inclines get a random grade in [-1, 1]. Weights must be
+    /// non-negative with a positive sum. A given `seed` reproduces the track.
+    #[staticmethod]
+    #[pyo3(signature = (track_length, weights, seed=None, finish_mode="all", segment_size=DEFAULT_SEGMENT_SIZE, max_ticks=DEFAULT_MAX_TICKS))]
+    pub fn new_with_distribution(
+        track_length: f32,
+        weights: HashMap<String, f32>,
+        seed: Option<u64>,
+        finish_mode: &str,
+        segment_size: f32,
+        max_ticks: u32,
+    ) -> PyResult<Self> {
+        check_track_length(track_length)?;
+        check_segment_size(segment_size)?;
+        let mut parsed = weights
+            .iter()
+            .map(|(name, &weight)| {
+                TerrainType::parse(name)
+                    .map(|terrain_type| (terrain_type, weight))
+                    .ok_or_else(|| PyValueError::new_err(format!("Unknown terrain type '{}'", name)))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        // Dict order is arbitrary; fix it so a seed always picks the same segments
+        parsed.sort_by_key(|(terrain_type, _)| TerrainType::ALL.iter().position(|t| t == terrain_type));
+        
+        let seed = seed.unwrap_or_else(rng::random_seed);
+        let mut inner = Race::with_distribution(track_length, seed, &parsed, segment_size, max_ticks)
+            .map_err(PyValueError::new_err)?;
+        inner.finish_mode = parse_finish_mode(finish_mode)?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Create a race on a track of nothing but normal terrain
+    /// Removes terrain luck, for comparing raw stats. A given `seed`
+    /// reproduces luck rolls
+    #[staticmethod]
+    #[pyo3(signature = (track_length, finish_mode="all", segment_size=DEFAULT_SEGMENT_SIZE, seed=None))]
+    pub fn flat_track(track_length: f32, finish_mode: &str, segment_size: f32, seed: Option<u64>) -> PyResult<Self> {
+        check_track_length(track_length)?;
+        check_segment_size(segment_size)?;
+        let seed = seed.unwrap_or_else(rng::random_seed);
+        let mut inner = Race::flat(track_length, seed, segment_size);
+        inner.finish_mode = parse_finish_mode(finish_mode)?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Create a race on a hand-authored track
+    /// Each segment is `(terrain_type, speed_modifier, energy_drain)` or,
+    /// for inclines, `(terrain_type, speed_modifier, energy_drain, grade)`;
+    /// unknown terrain types fall back to normal. Track length is
+    /// `segment_size` per segment
+    #[staticmethod]
+    #[pyo3(signature = (segments, finish_mode="all", segment_size=DEFAULT_SEGMENT_SIZE))]
+    pub fn from_track(segments: Vec<SegmentSpec>, finish_mode: &str, segment_size: f32) -> PyResult<Self> {
+        check_segment_size(segment_size)?;
+        if segments.is_empty() {
+            return Err(PyValueError::new_err("Track must have at least one segment"));
         }
+        let track = segments
+            .iter()
+            .map(|segment| match segment {
+                SegmentSpec::Graded(terrain_type, speed_mod, energy_drain, grade) => {
+                    Terrain::from_str(terrain_type, *speed_mod, *energy_drain).with_grade(*grade)
+                },
+                SegmentSpec::Flat(terrain_type, speed_mod, energy_drain) => {
+                    Terrain::from_str(terrain_type, *speed_mod, *energy_drain)
+                },
+            })
+            .collect();
+        let mut inner = Race::from_track(track, segment_size);
+        inner.finish_mode = parse_finish_mode(finish_mode)?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Seed the track and luck streams were generated from
+    #[getter]
+    pub fn seed(&self) -> u64 {
+        self.inner.seed
+    }
+    
+    /// Current weather: "clear", "rain", "heat" or "fog"
+    #[getter]
+    pub fn weather(&self) -> &'static str {
+        self.inner.weather.as_str()
+    }
+    
+    /// Set race-wide weather, scaling every segment while racing
+    ///
+    /// rain: speed x0.9 (mud x0.72), drain x1.1; heat: water x0.8 and ice
+    /// x0.85 speed, drain x1.2; fog: speed x0.95; clear: unchanged
+    pub fn set_weather(&mut self, weather: &str) -> PyResult<()> {
+        self.inner.weather = Weather::parse(weather)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown weather '{}'", weather)))?;
+        Ok(())
+    }
+    
+    /// Register a callable invoked before movement on every tick
+    ///
+    /// Called as `hook(tick, turtles)` where `turtles` is a list of dicts
+    /// with `name`, `distance`, `energy`, `is_resting` and `finished`.
+    /// It may return a dict of name -> speed multiplier (or None); unlisted
+    /// turtles move normally. Exceptions propagate out of `tick`/`run`.
+    ///
+    /// A hook costs one Python call per tick and keeps the GIL held for
+    /// the whole race, disabling the GIL-released fast path in `run`.
+    pub fn set_step_hook(&mut self, hook: PyObject) {
+        self.step_hook = Some(hook);
+    }
+    
+    /// Remove the step hook, restoring the fast path
+    pub fn clear_step_hook(&mut self) {
+        self.step_hook = None;
+    }
+    
+    /// Turn drafting on or off
+    ///
+    /// While on, a moving turtle at most `distance` behind another moves
+    /// 5% further that tick. Gaps are measured before anyone moves.
+    #[pyo3(signature = (enabled, distance=5.0))]
+    pub fn set_drafting(&mut self, enabled: bool, distance: f32) -> PyResult<()> {
+        if enabled && distance <= 0.0 {
+            return Err(PyValueError::new_err(format!("distance must be positive, got {}", distance)));
+        }
+        self.inner.draft_distance = enabled.then_some(distance);
+        Ok(())
+    }
+    
+    /// Turn terrain smoothing on or off (off by default)
+    ///
+    /// While on, speed and drain blend linearly from each segment's values
+    /// at its start to the next segment's at its end, so they no longer
+    /// jump at boundaries. The terrain type (and rest, drift and weather
+    /// rules that follow from it) is that of the nearer segment.
+    pub fn set_terrain_smoothing(&mut self, enabled: bool) {
+        self.inner.terrain_smoothing = enabled;
+    }
+    
+    /// Select how luck affects the race: "surge" (default), "continuous",
+    /// "critical" or "off"
+    pub fn set_luck_mode(&mut self, mode: &str) -> PyResult<()> {
+        self.inner.luck_mode = LuckMode::parse(mode)
+            .ok_or_else(|| PyValueError::new_err(format!("Unknown luck mode '{}'", mode)))?;
+        Ok(())
+    }
+    
+    /// Luck events (criticals and surges) from the last run as (tick, name, kind)
+    pub fn get_luck_events(&self) -> Vec<(u32, String, &'static str)> {
+        self.inner.luck_events
+            .iter()
+            .map(|e| (e.tick, self.inner.turtles[e.turtle].name.clone(), e.roll.as_str()))
+            .collect()
+    }
+    
+    /// Start recording every turtle's state after each tick
+    ///
+    /// Frames cost roughly one name string plus 8 bytes per turtle per
+    /// tick and accumulate until the next run or `clear_recording`, so a
+    /// long tournament should clear between races.
+    pub fn enable_recording(&mut self) {
+        self.inner.recording = true;
+    }
+    
+    /// Free recorded frames; recording stays enabled if it was
+    pub fn clear_recording(&mut self) {
+        self.inner.frames = Vec::new();
+    }
+    
+    /// Snapshot of every turtle in roster order (see `PyTurtle.snapshot`)
+    pub fn snapshot_all<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner.turtles
+            .iter()
+            .map(|turtle| turtle_snapshot(py, turtle))
+            .collect()
+    }
+    
+    /// Recorded frames, one per tick: list of (name, distance, is_resting)
+    pub fn get_replay(&self) -> Vec<Vec<(String, f32, bool)>> {
+        self.inner.frames.clone()
     }
     
     /// Add a turtle to the race
@@ -127,19 +633,437 @@ impl PyRace {
     }
     
     /// Run the full race
-    /// Returns winner name
-    pub fn run(&mut self) -> String {
-        self.inner.run()
+    /// Returns the winner's name, or None if the race has no turtles
+    pub fn run(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        if self.step_hook.is_none() {
+            let inner = &mut self.inner;
+            return Ok(py.allow_threads(|| inner.run()));
+        }
+        
+        self.inner.reset();
+        while !self.tick(py)? {}
+        Ok(self.inner.winner_name())
+    }
+    
+    /// How the winner was decided: "outright", "energy", "luck" or
+    /// "coin_flip" (photo-finish tie-breaks, in that order)
+    /// None before the race has started or if it has no turtles
+    pub fn get_last_winner_reason(&self) -> Option<&'static str> {
+        if self.inner.tick_count == 0 {
+            return None;
+        }
+        self.inner.winner().map(|(_, reason)| reason.as_str())
     }
     
     /// Run a single tick
     /// Returns true if race is finished
-    pub fn tick(&mut self) -> bool {
-        self.inner.tick()
+    pub fn tick(&mut self, py: Python<'_>) -> PyResult<bool> {
+        let multipliers = match &self.step_hook {
+            Some(hook) => self.call_step_hook(py, hook)?,
+            None => Vec::new(),
+        };
+        Ok(self.inner.tick_with_multipliers(&multipliers))
+    }
+    
+    /// True once the finish condition is met or max ticks are reached
+    #[getter]
+    pub fn is_over(&self) -> bool {
+        self.inner.is_over()
+    }
+    
+    /// Run a single tick and report each turtle's movement
+    ///
+    /// Returns `(name, delta, total_distance)` per turtle in roster order,
+    /// where `delta` is how far it moved this tick (0.0 once finished,
+    /// negative if mud slipped it back). Check `is_over` for the end of
+    /// the race.
+    pub fn tick_detailed(&mut self, py: Python<'_>) -> PyResult<Vec<(String, f32, f32)>> {
+        let multipliers = match &self.step_hook {
+            Some(hook) => self.call_step_hook(py, hook)?,
+            None => Vec::new(),
+        };
+        let (_, deltas) = self.inner.tick_with_deltas(&multipliers);
+        Ok(self.inner.turtles
+            .iter()
+            .zip(deltas)
+            .map(|(t, delta)| (t.name.clone(), delta, t.race_distance))
+            .collect())
     }
     
     /// Get current positions as list of (name, distance)
     pub fn get_positions(&self) -> Vec<(String, f32)> {
         self.inner.get_positions()
     }
+    
+    /// Record split times at these track fractions
+    ///
+    /// Fractions must be in (0, 1) and strictly increasing, e.g.
+    /// `[0.25, 0.5, 0.75]`. Replaces any previous checkpoints and clears
+    /// splits recorded so far.
+    pub fn set_checkpoints(&mut self, fractions: Vec<f32>) -> PyResult<()> {
+        if fractions.iter().any(|f| !(*f > 0.0 && *f < 1.0)) {
+            return Err(PyValueError::new_err("Checkpoint fractions must be in (0, 1)"));
+        }
+        if fractions.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(PyValueError::new_err("Checkpoint fractions must be strictly increasing"));
+        }
+        self.inner.checkpoints = fractions;
+        for turtle in &mut self.inner.turtles {
+            turtle.splits.clear();
+        }
+        Ok(())
+    }
+    
+    /// Tick each turtle first passed each checkpoint, keyed by name
+    /// One entry per checkpoint; None if not reached yet
+    pub fn get_splits(&self) -> HashMap<String, Vec<Option<u32>>> {
+        let count = self.inner.checkpoints.len();
+        self.inner.turtles
+            .iter()
+            .map(|t| {
+                let splits = (0..count).map(|i| t.splits.get(i).copied()).collect();
+                (t.name.clone(), splits)
+            })
+            .collect()
+    }
+    
+    /// Tick each turtle first ran out of energy and had to rest, keyed by
+    /// name; None for turtles that have paced themselves so far
+    pub fn get_bonk_events(&self) -> HashMap<String, Option<u32>> {
+        self.inner.turtles
+            .iter()
+            .map(|t| (t.name.clone(), t.first_bonk_tick))
+            .collect()
+    }
+    
+    /// How close the race has been, for picking highlights
+    ///
+    /// `lead_changes` counts ticks where a different turtle moved strictly
+    /// ahead of the leader; `closest_margin` and `max_margin` are the
+    /// smallest and largest gaps between 1st and 2nd after any tick (None
+    /// with fewer than two turtles or before the first tick). A blowout
+    /// has few changes and wide margins, a nail-biter the opposite.
+    pub fn get_excitement<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let excitement = &self.inner.excitement;
+        let dict = PyDict::new(py);
+        dict.set_item("lead_changes", excitement.lead_changes)?;
+        dict.set_item("closest_margin", excitement.closest_margin)?;
+        dict.set_item("max_margin", excitement.max_margin)?;
+        Ok(dict)
+    }
+    
+    /// Live remaining distance and ETA per turtle, in roster order
+    ///
+    /// Each dict has `name`, `remaining`, `avg_speed_recent` (mean
+    /// distance per tick over the last 10 ticks) and `eta_ticks`
+    /// (`remaining / avg_speed_recent`; 0.0 once finished, None while the
+    /// turtle rests or has not been moving forward).
+    pub fn get_projections<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner.projections()
+            .into_iter()
+            .map(|projection| {
+                let dict = PyDict::new(py);
+                dict.set_item("name", &self.inner.turtles[projection.turtle].name)?;
+                dict.set_item("remaining", projection.remaining)?;
+                dict.set_item("avg_speed_recent", projection.avg_speed)?;
+                dict.set_item("eta_ticks", projection.eta_ticks)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+    
+    /// Full standings as dicts sorted by placement
+    ///
+    /// Each has `name`, `place` (1-based, shared on ties), `finish_tick`
+    /// and `finish_time` (None if the turtle did not finish) and final
+    /// `distance`. Finishers rank by interpolated finish time.
+    pub fn get_results<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        self.inner.results()
+            .into_iter()
+            .map(|placement| placement_to_pydict(py, placement))
+            .collect()
+    }
+    
+    /// Run a multi-leg race where energy carries over between legs
+    ///
+    /// Each stage runs on its own seeded track of the given length until
+    /// every turtle finishes or `max_ticks` pass; only distance resets
+    /// between stages. Returns `standings` (dicts with `name`, `place`,
+    /// `total_time` and `stages_finished`, best first; an unfinished stage
+    /// adds its full tick count) and `stages` (per stage: `length`, `ticks`
+    /// and `results` as in `get_results`). The step hook is not called.
+    pub fn run_stages<'py>(&mut self, py: Python<'py>, stage_lengths: Vec<f32>) -> PyResult<Bound<'py, PyDict>> {
+        if stage_lengths.iter().any(|&length| length <= 0.0) {
+            return Err(PyValueError::new_err("Stage lengths must be positive"));
+        }
+        let inner = &mut self.inner;
+        let (standings, stages) = py.allow_threads(|| inner.run_stages(&stage_lengths));
+        
+        let dict = PyDict::new(py);
+        let standings = standings
+            .into_iter()
+            .map(|standing| {
+                let entry = PyDict::new(py);
+                entry.set_item("name", standing.name)?;
+                entry.set_item("place", standing.place)?;
+                entry.set_item("total_time", standing.total_time)?;
+                entry.set_item("stages_finished", standing.stages_finished)?;
+                Ok(entry)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let stages = stages
+            .into_iter()
+            .map(|stage| {
+                let entry = PyDict::new(py);
+                entry.set_item("length", stage.length)?;
+                entry.set_item("ticks", stage.ticks)?;
+                let results = stage.placements
+                    .into_iter()
+                    .map(|placement| placement_to_pydict(py, placement))
+                    .collect::<PyResult<Vec<_>>>()?;
+                entry.set_item("results", results)?;
+                Ok(entry)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        dict.set_item("standings", standings)?;
+        dict.set_item("stages", stages)?;
+        Ok(dict)
+    }
+    
+    /// Play a round-robin league between cloned turtles
+    ///
+    /// By default every pairing races once head to head, in parallel with
+    /// the GIL released, and a win is worth one point. With `single_race`
+    /// everyone runs one race together instead and scores a point per
+    /// opponent placed behind it. Each match runs on a track seeded from
+    /// `seed`, so a league replays exactly. Returns `standings` (dicts with
+    /// `name`, `wins`, `points` and `total_distance`, sorted by points then
+    /// total distance) and the `seed` used.
+    #[staticmethod]
+    #[pyo3(signature = (turtles, track_length, single_race=false, seed=None))]
+    pub fn round_robin<'py>(
+        py: Python<'py>,
+        turtles: Vec<PyRef<'py, PyTurtle>>,
+        track_length: f32,
+        single_race: bool,
+        seed: Option<u64>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        check_track_length(track_length)?;
+        let roster: Vec<Turtle> = turtles.iter().map(|t| t.inner.clone()).collect();
+        let seed = seed.unwrap_or_else(rng::random_seed);
+        let standings = py.allow_threads(|| round_robin(&roster, track_length, single_race, seed));
+        
+        let standings = standings
+            .into_iter()
+            .map(|standing| {
+                let entry = PyDict::new(py);
+                entry.set_item("name", &roster[standing.turtle].name)?;
+                entry.set_item("wins", standing.wins)?;
+                entry.set_item("points", standing.points)?;
+                entry.set_item("total_distance", standing.total_distance)?;
+                Ok(entry)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let dict = PyDict::new(py);
+        dict.set_item("standings", standings)?;
+        dict.set_item("seed", seed)?;
+        Ok(dict)
+    }
+    
+    /// Composition of the generated track
+    /// Segment counts per terrain type (every type present, zero if
+    /// unused), plus `segments` and `track_length`
+    pub fn get_track_summary<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for terrain_type in TerrainType::ALL {
+            let count = self.inner.track
+                .iter()
+                .filter(|t| t.terrain_type == terrain_type)
+                .count();
+            dict.set_item(terrain_type.as_str(), count)?;
+        }
+        dict.set_item("segments", self.inner.track.len())?;
+        dict.set_item("track_length", self.inner.track_length)?;
+        Ok(dict)
+    }
+    
+    /// Single 0-1 score for how punishing the track is
+    /// Weighs each segment's energy drain against its speed; an all-normal
+    /// track is ~0.33 and an all-mud track 1.0 (see `Race::difficulty_rating`)
+    pub fn difficulty_rating(&self) -> f32 {
+        self.inner.difficulty_rating()
+    }
+    
+    /// Finish data as (name, finish_tick, finish_time), earliest first
+    /// `finish_time` interpolates the crossing within its tick; both are
+    /// None for turtles that have not finished
+    pub fn get_finish_times(&self) -> Vec<(String, Option<u32>, Option<f32>)> {
+        let mut times: Vec<_> = self.inner.turtles
+            .iter()
+            .map(|t| (t.name.clone(), t.finish_tick, t.finish_time))
+            .collect();
+        times.sort_by(|a, b| match (a.2, b.2) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        times
+    }
+    
+    /// Capture the inputs needed to reproduce this race
+    /// Stores seed, track, roster and configuration; a step hook is not
+    /// captured, so replays of hooked races run without it
+    pub fn record_setup<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let setup = self.inner.record_setup()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &setup))
+    }
+    
+    /// Rebuild a race from `record_setup` bytes and rerun it
+    /// Returns the finished race; results match the original run
+    #[staticmethod]
+    pub fn replay(py: Python<'_>, setup_bytes: &[u8]) -> PyResult<Self> {
+        let inner = py.allow_threads(|| Race::replay(setup_bytes))
+            .map_err(|e| PyValueError::new_err(format!("Invalid race setup: {}", e)))?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Save the full race state (track, turtles, progress, luck streams)
+    /// A step hook is not saved
+    pub fn to_json(&self) -> PyResult<String> {
+        self.inner.to_json()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+    
+    /// Resume a race saved with `to_json`
+    /// Further ticks play out exactly as they would have without the save
+    #[staticmethod]
+    pub fn from_json(s: &str) -> PyResult<Self> {
+        let inner = Race::from_json(s)
+            .map_err(|e| PyValueError::new_err(format!("Invalid race JSON: {}", e)))?;
+        Ok(Self { inner, step_hook: None })
+    }
+    
+    /// Estimate each turtle's win probability via Monte Carlo
+    /// Runs `samples` seeded races with the GIL released; `fixed_track`
+    /// reuses this race's track instead of generating one per sample
+    #[pyo3(signature = (samples, seed, fixed_track=false))]
+    pub fn win_probabilities<'py>(&self, py: Python<'py>, samples: usize, seed: u64, fixed_track: bool) -> PyResult<Bound<'py, PyDict>> {
+        if samples == 0 {
+            return Err(PyValueError::new_err("samples must be at least 1"));
+        }
+        
+        self.odds(py, samples, seed, fixed_track)
+    }
+    
+    /// Pre-race win fraction per turtle name, summing to ~1.0
+    /// Like `win_probabilities`, but seeded randomly unless `seed` is given;
+    /// trials run in parallel with the GIL released
+    #[pyo3(signature = (trials, fixed_track=false, seed=None))]
+    pub fn simulate_odds<'py>(&self, py: Python<'py>, trials: usize, fixed_track: bool, seed: Option<u64>) -> PyResult<Bound<'py, PyDict>> {
+        if trials == 0 {
+            return Err(PyValueError::new_err("trials must be at least 1"));
+        }
+        self.odds(py, trials, seed.unwrap_or_else(rng::random_seed), fixed_track)
+    }
+}
+
+fn check_track_length(track_length: f32) -> PyResult<()> {
+    validate_length("track_length", track_length).map_err(PyValueError::new_err)
+}
+
+fn check_segment_size(segment_size: f32) -> PyResult<()> {
+    validate_length("segment_size", segment_size).map_err(PyValueError::new_err)
+}
+
+fn parse_finish_mode(mode: &str) -> PyResult<FinishMode> {
+    FinishMode::parse(mode)
+        .ok_or_else(|| PyValueError::new_err(format!("Unknown finish mode '{}'", mode)))
+}
+
+impl PyRace {
+    /// Run `samples` Monte Carlo races and return win fractions by name
+    fn odds<'py>(&self, py: Python<'py>, samples: usize, seed: u64, fixed_track: bool) -> PyResult<Bound<'py, PyDict>> {
+        let wins = py.allow_threads(|| self.inner.simulate_many(samples, seed, fixed_track));
+        
+        let dict = PyDict::new(py);
+        for (turtle, count) in self.inner.turtles.iter().zip(wins) {
+            dict.set_item(&turtle.name, count as f32 / samples as f32)?;
+        }
+        Ok(dict)
+    }
+    
+    /// Invoke the step hook and resolve its result into roster-order multipliers
+    fn call_step_hook(&self, py: Python<'_>, hook: &PyObject) -> PyResult<Vec<f32>> {
+        let turtles = PyList::empty(py);
+        for turtle in &self.inner.turtles {
+            let dict = PyDict::new(py);
+            dict.set_item("name", &turtle.name)?;
+            dict.set_item("distance", turtle.race_distance)?;
+            dict.set_item("energy", turtle.current_energy)?;
+            dict.set_item("is_resting", turtle.is_resting)?;
+            dict.set_item("finished", turtle.finished)?;
+            turtles.append(dict)?;
+        }
+        
+        let result = hook.call1(py, (self.inner.tick_count + 1, turtles))?;
+        let adjustments: Option<HashMap<String, f32>> = result.extract(py)?;
+        let adjustments = adjustments.unwrap_or_default();
+        
+        Ok(self.inner.turtles
+            .iter()
+            .map(|t| adjustments.get(&t.name).copied().unwrap_or(1.0))
+            .collect())
+    }
+}
+
+/// Derive new turtle ids deterministically from `seed`
+///
+/// The n-th turtle created afterwards gets the same id on every run.
+/// Pass None to go back to random ids (the default).
+#[pyfunction]
+#[pyo3(signature = (seed=None))]
+pub fn set_turtle_id_seed(seed: Option<u64>) {
+    set_id_seed(seed);
+}
+
+/// Run a single-elimination tournament of head-to-head races
+///
+/// Plays up to `rounds` rounds (matches within a round run in parallel
+/// with the GIL released) and returns `(champion, bracket)`. `bracket`
+/// lists each round's matches as dicts with `a`, `b` (None for a bye) and
+/// `winner` names; `champion` is None if the rounds ran out first. Each
+/// match races cloned turtles on a track seeded from `seed`, so the
+/// bracket is reproducible.
+#[pyfunction]
+#[pyo3(name = "run_tournament", signature = (turtles, track_length, rounds, seed=None))]
+#[allow(clippy::type_complexity)]
+pub fn run_tournament_py<'py>(
+    py: Python<'py>,
+    turtles: Vec<PyRef<'py, PyTurtle>>,
+    track_length: f32,
+    rounds: usize,
+    seed: Option<u64>,
+) -> PyResult<(Option<String>, Vec<Vec<Bound<'py, PyDict>>>)> {
+    let roster: Vec<Turtle> = turtles.iter().map(|t| t.inner.clone()).collect();
+    let seed = seed.unwrap_or_else(rng::random_seed);
+    let bracket = py.allow_threads(|| run_tournament(&roster, track_length, rounds, seed));
+    
+    let name = |i: usize| roster[i].name.clone();
+    let rounds = bracket.rounds
+        .iter()
+        .map(|matches| {
+            matches.iter()
+                .map(|m| {
+                    let dict = PyDict::new(py);
+                    dict.set_item("a", name(m.a))?;
+                    dict.set_item("b", m.b.map(name))?;
+                    dict.set_item("winner", name(m.winner))?;
+                    Ok(dict)
+                })
+                .collect::<PyResult<Vec<_>>>()
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok((bracket.champion.map(name), rounds))
 }
@@ -1,86 +1,676 @@
 //! Race simulation
 
-use super::turtle::Turtle;
-use super::terrain::Terrain;
+use rand::Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use super::turtle::{LuckMode, LuckRoll, Turtle};
+use super::terrain::{Terrain, TerrainType, Weather};
+use crate::rng::{self, derive_seed};
 
-const SEGMENT_SIZE: f32 = 50.0;
-const MAX_TICKS: u32 = 5000;
+pub const DEFAULT_SEGMENT_SIZE: f32 = 50.0;
+pub const DEFAULT_MAX_TICKS: u32 = 5000;
+/// Mean segment difficulty that rates 1.0 (an all-mud track)
+const MAX_TRACK_DIFFICULTY: f32 = 3.0;
+/// Move multiplier for a turtle drafting behind another
+pub const DRAFT_BONUS: f32 = 1.05;
+
+fn default_segment_size() -> f32 {
+    DEFAULT_SEGMENT_SIZE
+}
+
+fn default_max_ticks() -> u32 {
+    DEFAULT_MAX_TICKS
+}
+
+/// Saves from before finish modes always stopped at the first finisher
+fn legacy_finish_mode() -> FinishMode {
+    FinishMode::FirstAcross
+}
+
+/// A luck roll (critical or surge) recorded during a race
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LuckEvent {
+    pub tick: u32,
+    pub turtle: usize,
+    #[serde(alias = "critical")]
+    pub roll: LuckRoll,
+}
+
+/// When a race is over
+/// Defaults to `AllFinish`, matching `PyRace(finish_mode="all")`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FinishMode {
+    /// Stop as soon as any turtle crosses the line
+    FirstAcross,
+    /// Keep going until every turtle has finished
+    #[default]
+    AllFinish,
+}
+
+impl FinishMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "first" => Some(FinishMode::FirstAcross),
+            "all" => Some(FinishMode::AllFinish),
+            _ => None,
+        }
+    }
+}
+
+/// How a race winner was decided
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WinReason {
+    /// Alone in first place
+    Outright,
+    /// Tied for first; most remaining energy
+    Energy,
+    /// Tied on energy too; highest luck
+    Luck,
+    /// Tied on everything; seeded coin flip
+    CoinFlip,
+}
+
+impl WinReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WinReason::Outright => "outright",
+            WinReason::Energy => "energy",
+            WinReason::Luck => "luck",
+            WinReason::CoinFlip => "coin_flip",
+        }
+    }
+}
+
+/// A turtle's final standing in a race
+#[derive(Clone, Debug)]
+pub struct Placement {
+    /// Roster index
+    pub turtle: usize,
+    pub name: String,
+    /// 1-based; turtles tied on time (or distance, if unfinished) share it
+    pub place: usize,
+    pub finish_tick: Option<u32>,
+    pub finish_time: Option<f32>,
+    pub distance: f32,
+}
+
+/// Lead-change bookkeeping for rating how close a race was
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Excitement {
+    /// Times a different turtle moved strictly into first place
+    pub lead_changes: u32,
+    /// Smallest and largest gap between 1st and 2nd after any tick
+    pub closest_margin: Option<f32>,
+    pub max_margin: Option<f32>,
+    /// Roster index of the current leader
+    pub leader: Option<usize>,
+}
+
+impl Excitement {
+    /// Update from this tick's distances, in roster order
+    fn record(&mut self, distances: &[f32]) {
+        let Some((first, &lead)) = distances.iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1).then(b.0.cmp(&a.0)))
+        else {
+            return;
+        };
+        match self.leader {
+            Some(current) if current != first && distances[current] < lead => {
+                self.lead_changes += 1;
+                self.leader = Some(first);
+            },
+            Some(_) => {},
+            None => self.leader = Some(first),
+        }
+        
+        let second = distances.iter()
+            .enumerate()
+            .filter(|&(i, _)| i != first)
+            .map(|(_, &d)| d)
+            .fold(f32::NEG_INFINITY, f32::max);
+        if second.is_finite() {
+            let margin = lead - second;
+            self.closest_margin = Some(self.closest_margin.map_or(margin, |m| m.min(margin)));
+            self.max_margin = Some(self.max_margin.map_or(margin, |m| m.max(margin)));
+        }
+    }
+}
+
+/// Projected finish for a turtle mid-race
+#[derive(Clone, Debug)]
+pub struct Projection {
+    /// Roster index
+    pub turtle: usize,
+    pub remaining: f32,
+    /// Mean distance per tick over the recent window
+    pub avg_speed: f32,
+    /// Ticks to the line at `avg_speed`; None while resting or stalled
+    pub eta_ticks: Option<f32>,
+}
+
+/// One leg of a staged race
+#[derive(Clone, Debug)]
+pub struct StageResult {
+    pub length: f32,
+    /// Ticks the stage took (capped at `max_ticks`)
+    pub ticks: u32,
+    pub placements: Vec<Placement>,
+}
+
+/// A turtle's overall standing across every stage
+#[derive(Clone, Debug)]
+pub struct StageStanding {
+    pub turtle: usize,
+    pub name: String,
+    /// 1-based; equal total times share a place
+    pub place: usize,
+    /// Sum of stage finish times; an unfinished stage counts its full ticks
+    pub total_time: f32,
+    pub stages_finished: usize,
+}
 
 /// Race manager
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Race {
     pub track: Vec<Terrain>,
     pub turtles: Vec<Turtle>,
     pub track_length: f32,
     pub tick_count: u32,
+    pub seed: u64,
+    pub luck_mode: LuckMode,
+    pub luck_events: Vec<LuckEvent>,
+    #[serde(default = "legacy_finish_mode")]
+    pub finish_mode: FinishMode,
+    /// Record a frame of every turtle's state after each tick
+    #[serde(default)]
+    pub recording: bool,
+    /// Recorded frames: (name, distance, is_resting) per turtle per tick
+    #[serde(skip)]
+    pub frames: Vec<Vec<(String, f32, bool)>>,
+    /// Length of each terrain segment
+    #[serde(default = "default_segment_size")]
+    pub segment_size: f32,
+    /// Tick cap after which the race ends regardless
+    #[serde(default = "default_max_ticks")]
+    pub max_ticks: u32,
+    /// Scales every segment's speed and drain while racing
+    #[serde(default)]
+    pub weather: Weather,
+    /// Slipstream range: a moving turtle at most this far behind another
+    /// gets `DRAFT_BONUS` for the tick. None disables drafting
+    #[serde(default)]
+    pub draft_distance: Option<f32>,
+    /// Track fractions, strictly increasing in (0, 1), at which split
+    /// times are recorded
+    #[serde(default)]
+    pub checkpoints: Vec<f32>,
+    /// Lead changes and margins so far this race
+    #[serde(default)]
+    pub excitement: Excitement,
+    /// Blend speed and drain between neighbouring segments instead of
+    /// switching at segment boundaries
+    #[serde(default)]
+    pub terrain_smoothing: bool,
 }
 
 impl Race {
     pub fn new(track_length: f32) -> Self {
-        let track = Terrain::generate_track(track_length, SEGMENT_SIZE);
+        Self::with_seed(track_length, rng::random_seed())
+    }
+    
+    /// Create a race whose track is generated from a fixed seed
+    pub fn with_seed(track_length: f32, seed: u64) -> Self {
+        Self::with_params(track_length, seed, DEFAULT_SEGMENT_SIZE, DEFAULT_MAX_TICKS)
+    }
+    
+    /// Create a seeded race with custom segment size and tick cap
+    /// `segment_size` must be positive
+    pub fn with_params(track_length: f32, seed: u64, segment_size: f32, max_ticks: u32) -> Self {
+        let track = Terrain::generate_track(track_length, segment_size, &mut rng::seeded(seed));
+        Self::with_track(track, track_length, seed, segment_size, max_ticks)
+    }
+    
+    /// Create a race on an already built track
+    fn with_track(track: Vec<Terrain>, track_length: f32, seed: u64, segment_size: f32, max_ticks: u32) -> Self {
         Self {
             track,
             turtles: Vec::new(),
             track_length,
             tick_count: 0,
+            seed,
+            luck_mode: LuckMode::default(),
+            luck_events: Vec::new(),
+            finish_mode: FinishMode::default(),
+            recording: false,
+            frames: Vec::new(),
+            segment_size,
+            max_ticks,
+            weather: Weather::default(),
+            draft_distance: None,
+            checkpoints: Vec::new(),
+            excitement: Excitement::default(),
+            terrain_smoothing: false,
         }
     }
     
+    /// Create a seeded race whose segments are sampled from `weights`
+    /// See `Terrain::generate_weighted` for how weights are applied
+    pub fn with_distribution(
+        track_length: f32,
+        seed: u64,
+        weights: &[(TerrainType, f32)],
+        segment_size: f32,
+        max_ticks: u32,
+    ) -> Result<Self, String> {
+        let track = Terrain::generate_weighted(track_length, segment_size, weights, &mut rng::seeded(seed))?;
+        Ok(Self::with_track(track, track_length, seed, segment_size, max_ticks))
+    }
+    
+    /// Create a race on all-normal terrain, so only stats separate turtles
+    /// Uses the same segment count as a generated track; `seed` drives luck
+    pub fn flat(track_length: f32, seed: u64, segment_size: f32) -> Self {
+        let segments = (track_length / segment_size).ceil() as usize;
+        Self::with_track(vec![Terrain::normal(); segments], track_length, seed, segment_size, DEFAULT_MAX_TICKS)
+    }
+    
+    /// Create a race on a hand-authored track
+    /// Track length is one `segment_size` per segment; luck is seeded randomly
+    pub fn from_track(track: Vec<Terrain>, segment_size: f32) -> Self {
+        let track_length = track.len() as f32 * segment_size;
+        Self::with_track(track, track_length, rng::random_seed(), segment_size, DEFAULT_MAX_TICKS)
+    }
+    
     pub fn add_turtle(&mut self, turtle: Turtle) {
         self.turtles.push(turtle);
     }
     
-    /// Get terrain at a given distance
-    fn get_terrain_at(&self, distance: f32) -> Terrain {
-        let segment_idx = (distance / SEGMENT_SIZE) as usize;
-        self.track[segment_idx.min(self.track.len() - 1)].clone()
+    /// Terrain at a given distance, as modified by the weather and
+    /// blended with the next segment when smoothing is on
+    pub fn get_terrain_at(&self, distance: f32) -> Terrain {
+        sample_terrain(&self.track, self.segment_size, self.weather, distance, self.terrain_smoothing)
+    }
+    
+    /// How punishing the track is, from 0.0 to 1.0
+    ///
+    /// Mean `Terrain::difficulty` over the segments divided by 3.0 (all mud)
+    /// and clamped, so an all-normal track rates ~0.33 and boost or ice
+    /// pull the rating down. An empty track rates 0.0.
+    pub fn difficulty_rating(&self) -> f32 {
+        if self.track.is_empty() {
+            return 0.0;
+        }
+        let mean = self.track.iter().map(Terrain::difficulty).sum::<f32>() / self.track.len() as f32;
+        (mean / MAX_TRACK_DIFFICULTY).clamp(0.0, 1.0)
     }
     
     /// Run a single simulation tick
     /// Returns true if race is finished
     pub fn tick(&mut self) -> bool {
+        self.tick_with_multipliers(&[])
+    }
+    
+    /// Run a single tick, scaling each turtle's movement by a multiplier
+    /// Multipliers are in roster order; missing entries default to 1.0
+    pub fn tick_with_multipliers(&mut self, multipliers: &[f32]) -> bool {
+        self.tick_with_deltas(multipliers).0
+    }
+    
+    /// Run a single tick and also return how far each turtle moved
+    ///
+    /// Deltas are in roster order: the change in `race_distance` this
+    /// tick (0.0 for turtles already finished). They can be negative when
+    /// mud slips a resting turtle back.
+    pub fn tick_with_deltas(&mut self, multipliers: &[f32]) -> (bool, Vec<f32>) {
         self.tick_count += 1;
+        let mut deltas = vec![0.0; self.turtles.len()];
         
-        // Collect terrain for each turtle first to avoid borrow issues
-        let terrains: Vec<Terrain> = self.turtles
-            .iter()
-            .map(|t| self.get_terrain_at(t.race_distance))
-            .collect();
+        // Gaps are measured before anyone moves this tick
+        let drafting = self.drafting_turtles();
         
-        for (turtle, terrain) in self.turtles.iter_mut().zip(terrains.iter()) {
+        // Borrow the track separately from the turtles while they are mutated
+        let track = &self.track;
+        for (i, turtle) in self.turtles.iter_mut().enumerate() {
             if turtle.finished {
                 continue;
             }
             
-            let distance = turtle.update_physics(terrain);
-            turtle.race_distance += distance;
+            let terrain = sample_terrain(track, self.segment_size, self.weather, turtle.race_distance, self.terrain_smoothing);
+            let mut multiplier = multipliers.get(i).copied().unwrap_or(1.0);
+            let was_resting = turtle.is_resting;
+            let outcome = turtle.step(&terrain, self.luck_mode);
+            if let Some(roll) = outcome.roll {
+                self.luck_events.push(LuckEvent { tick: self.tick_count, turtle: i, roll });
+            }
+            if drafting[i] && !was_resting {
+                multiplier *= DRAFT_BONUS;
+            }
+            // Resting only ever starts when energy runs out
+            if turtle.is_resting && !was_resting && turtle.first_bonk_tick.is_none() {
+                turtle.first_bonk_tick = Some(self.tick_count);
+            }
+            let distance = outcome.distance * multiplier;
+            let previous = turtle.race_distance;
+            turtle.race_distance = (previous + distance).max(0.0);
+            deltas[i] = turtle.race_distance - previous;
+            turtle.record_delta(deltas[i]);
+            // A turtle can pass several checkpoints in one tick
+            while let Some(fraction) = self.checkpoints.get(turtle.splits.len()) {
+                if turtle.race_distance < fraction * self.track_length {
+                    break;
+                }
+                turtle.splits.push(self.tick_count);
+            }
             
             if turtle.race_distance >= self.track_length {
                 turtle.finished = true;
+                turtle.finish_tick = Some(self.tick_count);
+                turtle.finish_time = Some(crossing_time(self.tick_count, previous, distance, self.track_length));
             }
         }
         
-        // Check if any turtle finished or max ticks reached
-        self.turtles.iter().any(|t| t.finished) || self.tick_count >= MAX_TICKS
+        let distances: Vec<f32> = self.turtles.iter().map(|t| t.race_distance).collect();
+        self.excitement.record(&distances);
+        
+        if self.recording {
+            self.frames.push(self.turtles
+                .iter()
+                .map(|t| (t.name.clone(), t.race_distance, t.is_resting))
+                .collect());
+        }
+        
+        let over = self.is_over();
+        if over {
+            for turtle in &mut self.turtles {
+                turtle.end_race();
+            }
+        }
+        (over, deltas)
+    }
+    
+    /// Whether the finish condition is met or max ticks reached
+    pub fn is_over(&self) -> bool {
+        let finished = match self.finish_mode {
+            FinishMode::FirstAcross => self.turtles.iter().any(|t| t.finished),
+            FinishMode::AllFinish => self.turtles.iter().all(|t| t.finished),
+        };
+        finished || self.tick_count >= self.max_ticks
+    }
+    
+    /// Which turtles sit within `draft_distance` behind another turtle
+    /// All false when drafting is off; level turtles don't draft each other
+    fn drafting_turtles(&self) -> Vec<bool> {
+        let Some(range) = self.draft_distance else {
+            return vec![false; self.turtles.len()];
+        };
+        self.turtles
+            .iter()
+            .map(|turtle| {
+                self.turtles.iter().any(|other| {
+                    let gap = other.race_distance - turtle.race_distance;
+                    gap > 0.0 && gap <= range
+                })
+            })
+            .collect()
     }
     
     /// Run the full race
-    /// Returns winner name
-    pub fn run(&mut self) -> String {
-        // Reset all turtles
-        for turtle in &mut self.turtles {
+    /// Returns the winner's name, or None if the race has no turtles
+    pub fn run(&mut self) -> Option<String> {
+        self.run_to_end();
+        self.winner_name()
+    }
+    
+    /// Name of the winner, after any photo-finish tie-break
+    /// None only when the race has no turtles
+    pub fn winner_name(&self) -> Option<String> {
+        self.winner().map(|(idx, _)| self.turtles[idx].name.clone())
+    }
+    
+    /// Roster index of the winner and how it was decided
+    /// The coin flip is seeded from the race seed, so replays agree
+    pub fn winner(&self) -> Option<(usize, WinReason)> {
+        self.photo_finish(&mut rng::seeded(self.seed))
+    }
+    
+    /// Reset all turtles and the tick counter for a fresh run
+    /// Each turtle's luck stream is reseeded from the race seed and its
+    /// roster index, so a seeded race replays identically
+    pub fn reset(&mut self) {
+        for (i, turtle) in self.turtles.iter_mut().enumerate() {
             turtle.reset_for_race();
+            turtle.luck_rng = rng::seeded(derive_seed(self.seed, i as u64));
         }
         
         self.tick_count = 0;
-        
+        self.luck_events.clear();
+        self.excitement = Excitement::default();
+        self.frames.clear();
+    }
+    
+    /// Reset and tick until the race is over
+    fn run_to_end(&mut self) {
+        self.reset();
         while !self.tick() {}
+    }
+    
+    /// Run a multi-leg race, carrying each turtle's energy between legs
+    ///
+    /// Stage `i` runs on a fresh track of `stage_lengths[i]` generated from
+    /// `derive_seed(seed, i)` until every turtle finishes or `max_ticks`
+    /// pass. Only the first stage restores energy; later stages reset just
+    /// distance and finish state. Overall standings rank by total time.
+    /// The race's own track and settings are restored afterwards and the
+    /// race is reset, so no stage state leaks onto the restored track.
+    pub fn run_stages(&mut self, stage_lengths: &[f32]) -> (Vec<StageStanding>, Vec<StageResult>) {
+        let track = std::mem::take(&mut self.track);
+        let track_length = self.track_length;
+        let finish_mode = self.finish_mode;
+        
+        self.reset();
+        self.finish_mode = FinishMode::AllFinish;
+        let mut totals = vec![(0.0_f32, 0_usize); self.turtles.len()];
+        let mut stages = Vec::with_capacity(stage_lengths.len());
+        
+        for (i, &length) in stage_lengths.iter().enumerate() {
+            let mut stage_rng = rng::seeded(derive_seed(self.seed, i as u64));
+            self.track = Terrain::generate_track(length, self.segment_size, &mut stage_rng);
+            self.track_length = length;
+            self.tick_count = 0;
+            for turtle in &mut self.turtles {
+                turtle.reset_for_stage();
+            }
+            
+            while !self.tick() {}
+            
+            for (total, turtle) in totals.iter_mut().zip(&self.turtles) {
+                match turtle.finish_time {
+                    Some(time) => {
+                        total.0 += time;
+                        total.1 += 1;
+                    },
+                    None => total.0 += self.tick_count as f32,
+                }
+            }
+            stages.push(StageResult { length, ticks: self.tick_count, placements: self.results() });
+        }
+        
+        self.track = track;
+        self.track_length = track_length;
+        self.finish_mode = finish_mode;
+        self.reset();
+        
+        let mut order: Vec<usize> = (0..self.turtles.len()).collect();
+        order.sort_by(|&a, &b| totals[a].0.total_cmp(&totals[b].0));
+        let mut standings: Vec<StageStanding> = Vec::with_capacity(order.len());
+        for (i, &index) in order.iter().enumerate() {
+            let (total_time, stages_finished) = totals[index];
+            let place = match standings.last() {
+                Some(prev) if prev.total_time == total_time => prev.place,
+                _ => i + 1,
+            };
+            standings.push(StageStanding {
+                turtle: index,
+                name: self.turtles[index].name.clone(),
+                place,
+                total_time,
+                stages_finished,
+            });
+        }
+        
+        (standings, stages)
+    }
+    
+    /// Serialize everything needed to rerun this race: seed, track,
+    /// roster and configuration (race progress is reset)
+    ///
+    /// Each turtle's `luck_rng` is part of the output. Setups written
+    /// before luck streams were saved lack it and still replay identically,
+    /// since `replay` reseeds every stream from the race seed.
+    pub fn record_setup(&self) -> serde_json::Result<Vec<u8>> {
+        let mut setup = self.clone();
+        setup.reset();
+        serde_json::to_vec(&setup)
+    }
+    
+    /// Serialize the race as it stands, mid-race progress and luck
+    /// streams included, so `from_json` can resume it exactly
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+    
+    /// Restore a race saved with `to_json`; ticking continues where it
+    /// left off (recorded frames are not saved)
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+    
+    /// Rebuild a race from `record_setup` output and run it to completion
+    /// Seeded luck streams make the rerun identical to the original
+    pub fn replay(setup: &[u8]) -> serde_json::Result<Self> {
+        let mut race: Race = serde_json::from_slice(setup)?;
+        race.run();
+        Ok(race)
+    }
+    
+    /// Run `samples` independent races and count wins per turtle
+    ///
+    /// Sample `i` uses the sub-seed `derive_seed(seed, i)`. With `fixed_track`
+    /// every sample races on this race's track; otherwise a fresh track is
+    /// generated from the sub-seed. Exact ties for the lead go through the
+    /// photo finish, so identical turtles share wins evenly. Samples run
+    /// in parallel; counts are returned in roster order.
+    pub fn simulate_many(&self, samples: usize, seed: u64, fixed_track: bool) -> Vec<u32> {
+        let winners: Vec<Option<usize>> = (0..samples).into_par_iter()
+            .map(|i| {
+                let sample_seed = derive_seed(seed, i as u64);
+                let mut race = if fixed_track {
+                    let mut race = self.clone();
+                    race.seed = sample_seed;
+                    race
+                } else {
+                    let mut race = Race::with_params(self.track_length, sample_seed, self.segment_size, self.max_ticks);
+                    race.turtles = self.turtles.clone();
+                    race
+                };
+                // Only the winner matters, and it is settled at the first crossing
+                race.finish_mode = FinishMode::FirstAcross;
+                
+                race.run_to_end();
+                race.winner().map(|(idx, _)| idx)
+            })
+            .collect();
+        
+        let mut wins = vec![0u32; self.turtles.len()];
+        for idx in winners.into_iter().flatten() {
+            wins[idx] += 1;
+        }
+        wins
+    }
+    
+    /// Pick one winner among the first-placed turtles
+    ///
+    /// Ties go to the most remaining energy, then the highest luck, then
+    /// a coin flip drawn from `rng`.
+    fn photo_finish<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<(usize, WinReason)> {
+        let mut leaders: Vec<usize> = self.results()
+            .iter()
+            .filter(|p| p.place == 1)
+            .map(|p| p.turtle)
+            .collect();
+        
+        if leaders.len() <= 1 {
+            return leaders.first().map(|&idx| (idx, WinReason::Outright));
+        }
+        
+        let energy: Vec<f32> = self.turtles.iter().map(|t| t.current_energy).collect();
+        let luck: Vec<f32> = self.turtles.iter().map(|t| t.stats.luck).collect();
+        for (values, reason) in [(energy, WinReason::Energy), (luck, WinReason::Luck)] {
+            let best = leaders.iter().map(|&i| values[i]).fold(f32::NEG_INFINITY, f32::max);
+            leaders.retain(|&i| values[i] == best);
+            if leaders.len() == 1 {
+                return Some((leaders[0], reason));
+            }
+        }
+        
+        Some((leaders[rng.gen_range(0..leaders.len())], WinReason::CoinFlip))
+    }
+    
+    /// Standings: finishers by finish time, then the rest by distance
+    pub fn results(&self) -> Vec<Placement> {
+        let mut order: Vec<(usize, &Turtle)> = self.turtles.iter().enumerate().collect();
+        order.sort_by(|(_, a), (_, b)| match (a.finish_time, b.finish_time) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => b.race_distance.total_cmp(&a.race_distance),
+        });
         
-        // Find winner (furthest distance)
+        let mut results: Vec<Placement> = Vec::with_capacity(order.len());
+        for (i, &(index, turtle)) in order.iter().enumerate() {
+            let tied = i > 0 && {
+                let prev = order[i - 1].1;
+                match (prev.finish_time, turtle.finish_time) {
+                    (Some(x), Some(y)) => x == y,
+                    (None, None) => prev.race_distance == turtle.race_distance,
+                    _ => false,
+                }
+            };
+            let place = if tied { results[i - 1].place } else { i + 1 };
+            results.push(Placement {
+                turtle: index,
+                name: turtle.name.clone(),
+                place,
+                finish_tick: turtle.finish_tick,
+                finish_time: turtle.finish_time,
+                distance: turtle.race_distance,
+            });
+        }
+        results
+    }
+    
+    /// Remaining distance and ETA for every turtle, in roster order
+    ///
+    /// Speed is averaged over the last `PROJECTION_WINDOW` ticks. The ETA
+    /// is `remaining / avg_speed`: 0.0 once finished, None while the turtle
+    /// rests or its average speed is not positive.
+    pub fn projections(&self) -> Vec<Projection> {
         self.turtles
             .iter()
-            .max_by(|a, b| a.race_distance.partial_cmp(&b.race_distance).unwrap())
-            .map(|t| t.name.clone())
-            .unwrap_or_else(|| "DRAW".to_string())
+            .enumerate()
+            .map(|(turtle, t)| {
+                let remaining = (self.track_length - t.race_distance).max(0.0);
+                let avg_speed = t.recent_speed();
+                let eta_ticks = if t.finished {
+                    Some(0.0)
+                } else if t.is_resting || avg_speed <= 0.0 {
+                    None
+                } else {
+                    Some(remaining / avg_speed)
+                };
+                Projection { turtle, remaining, avg_speed, eta_ticks }
+            })
+            .collect()
     }
     
     /// Get current positions sorted by distance
@@ -90,7 +680,371 @@ impl Race {
             .map(|t| (t.name.clone(), t.race_distance))
             .collect();
         
-        positions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        positions.sort_by(|a, b| b.1.total_cmp(&a.1));
         positions
     }
 }
+
+/// Fractional tick at which a turtle crossed `track_length`
+///
+/// Assumes constant speed within the tick: a turtle that needed 40% of
+/// this tick's movement to reach the line finishes at `tick - 1 + 0.4`.
+/// Check a track length or segment size before building a track
+/// Must be finite and positive: an infinite length would ask
+/// `Terrain::generate_track` for `usize::MAX` segments
+pub fn validate_length(name: &str, value: f32) -> Result<(), String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{} must be positive and finite, got {}", name, value))
+    }
+}
+
+fn crossing_time(tick: u32, previous: f32, moved: f32, track_length: f32) -> f32 {
+    let fraction = if moved > 0.0 {
+        ((track_length - previous) / moved).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    (tick - 1) as f32 + fraction
+}
+
+/// Terrain used where a track has no segments
+static FLAT_GROUND: Terrain = Terrain {
+    terrain_type: TerrainType::Normal,
+    speed_modifier: 1.0,
+    energy_drain: 1.0,
+    grade: 0.0,
+};
+
+/// Segment of `track` containing `distance` (normal ground if empty)
+fn terrain_at(track: &[Terrain], segment_size: f32, distance: f32) -> &Terrain {
+    let segment_idx = (distance / segment_size) as usize;
+    track.get(segment_idx.min(track.len().saturating_sub(1))).unwrap_or(&FLAT_GROUND)
+}
+
+/// Terrain a turtle at `distance` races on, under `weather`
+///
+/// Without `smooth` this is just the containing segment. With it, speed
+/// and drain fade linearly from the containing segment's values at its
+/// start to the next segment's at its end, so they are continuous across
+/// every boundary; type and grade are those of whichever segment is
+/// nearer. The last segment has nothing to blend towards and plays as is.
+fn sample_terrain(track: &[Terrain], segment_size: f32, weather: Weather, distance: f32, smooth: bool) -> Terrain {
+    let current = weather.apply(terrain_at(track, segment_size, distance));
+    let position = distance / segment_size;
+    let next = match track.get(position as usize + 1) {
+        Some(next) if smooth => weather.apply(next),
+        _ => return current,
+    };
+    
+    let t = position.fract();
+    let mut blended = if t < 0.5 { current } else { next };
+    blended.speed_modifier = current.speed_modifier + (next.speed_modifier - current.speed_modifier) * t;
+    blended.energy_drain = current.energy_drain + (next.energy_drain - current.energy_drain) * t;
+    blended
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TurtleStats;
+    
+    fn race_with(count: usize) -> Race {
+        let mut race = Race::with_seed(500.0, 7);
+        for i in 0..count {
+            race.add_turtle(Turtle::with_id(format!("t{}", i), format!("{:08}", i), TurtleStats::default()));
+        }
+        race
+    }
+    
+    #[test]
+    fn identical_turtles_get_near_uniform_odds() {
+        let samples = 4000;
+        for fixed_track in [false, true] {
+            let wins = race_with(4).simulate_many(samples, 42, fixed_track);
+            assert_eq!(wins.iter().sum::<u32>(), samples as u32);
+            for &w in &wins {
+                // 0.25 +/- 0.04 is over six standard deviations at 4000 samples
+                let p = w as f32 / samples as f32;
+                assert!((p - 0.25).abs() < 0.04, "{:?}", wins);
+            }
+        }
+    }
+    
+    #[test]
+    fn simulate_many_is_deterministic_under_seed() {
+        let race = race_with(3);
+        assert_eq!(race.simulate_many(500, 9, false), race.simulate_many(500, 9, false));
+    }
+    
+    /// Segment lookup as a linear scan over segment end points
+    fn linear_terrain_at(track: &[Terrain], segment_size: f32, distance: f32) -> Option<&Terrain> {
+        track.iter()
+            .enumerate()
+            .find(|&(i, _)| distance < (i + 1) as f32 * segment_size)
+            .map(|(_, terrain)| terrain)
+            .or(track.last())
+    }
+    
+    #[test]
+    fn terrain_lookup_matches_linear_scan_everywhere() {
+        let race = Race::with_seed(1000.0, 3);
+        let mut distance = 0.0;
+        while distance <= race.track_length + 100.0 {
+            let expected = linear_terrain_at(&race.track, race.segment_size, distance).unwrap();
+            let got = terrain_at(&race.track, race.segment_size, distance);
+            assert!(std::ptr::eq(expected, got), "segment mismatch at {}", distance);
+            distance += 0.25;
+        }
+    }
+    
+    #[test]
+    fn empty_track_races_on_flat_ground() {
+        let mut race = Race::from_track(Vec::new(), DEFAULT_SEGMENT_SIZE);
+        let terrain = race.get_terrain_at(10.0);
+        assert_eq!(terrain.terrain_type, TerrainType::Normal);
+        assert_eq!(terrain.speed_modifier, 1.0);
+        
+        race.add_turtle(Turtle::with_id("solo".to_string(), "00000000".to_string(), TurtleStats::default()));
+        assert_eq!(race.run(), Some("solo".to_string()));
+    }
+    
+    /// `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_50_turtles_5000_ticks() {
+        let mut race = Race::with_params(1e7, 1, DEFAULT_SEGMENT_SIZE, 5000);
+        for i in 0..50 {
+            race.add_turtle(Turtle::with_id(format!("t{}", i), format!("{:08}", i), TurtleStats::default()));
+        }
+        race.reset();
+        let start = std::time::Instant::now();
+        while !race.tick() {}
+        println!("50 turtles x {} ticks: {:?}", race.tick_count, start.elapsed());
+        assert_eq!(race.tick_count, 5000);
+    }
+    
+    #[test]
+    fn sub_tick_times_order_same_tick_finishers() {
+        let mut race = Race::from_track(vec![Terrain::normal(); 2], DEFAULT_SEGMENT_SIZE);
+        for (name, speed) in [("steady", 6.0), ("quick", 6.1)] {
+            let stats = TurtleStats { speed, ..TurtleStats::default() };
+            race.add_turtle(Turtle::with_id(name.to_string(), "00000000".to_string(), stats));
+        }
+        race.run();
+        
+        let (steady, quick) = (&race.turtles[0], &race.turtles[1]);
+        assert_eq!(steady.finish_tick, quick.finish_tick);
+        assert!(quick.finish_time.unwrap() < steady.finish_time.unwrap());
+        let tick = quick.finish_tick.unwrap() as f32;
+        assert!(quick.finish_time.unwrap() > tick - 1.0 && steady.finish_time.unwrap() <= tick);
+        assert_eq!(race.results()[0].name, "quick");
+        assert_eq!(race.winner_name(), Some("quick".to_string()));
+    }
+    
+    /// Every field of every placement, exactly
+    fn summary(race: &Race) -> String {
+        format!("{:?}", race.results())
+    }
+    
+    #[test]
+    fn replay_reproduces_results_exactly() {
+        let mut race = race_with(4);
+        race.luck_mode = LuckMode::Critical;
+        race.finish_mode = FinishMode::AllFinish;
+        race.run();
+        
+        let replayed = Race::replay(&race.record_setup().unwrap()).unwrap();
+        assert_eq!(summary(&replayed), summary(&race));
+        assert_eq!(replayed.tick_count, race.tick_count);
+        assert_eq!(replayed.luck_events.len(), race.luck_events.len());
+    }
+    
+    #[test]
+    fn ice_track_never_rests_and_arrives_exhausted() {
+        let solo = |terrain: Terrain| {
+            let mut race = Race::from_track(vec![terrain; 100], DEFAULT_SEGMENT_SIZE);
+            race.add_turtle(Turtle::with_id("skater".to_string(), "00000000".to_string(), TurtleStats::default()));
+            race.reset();
+            race
+        };
+        
+        let mut ice = solo(Terrain::ice());
+        while !ice.tick() {
+            assert!(!ice.turtles[0].is_resting, "rested on ice at tick {}", ice.tick_count);
+        }
+        let skater = &ice.turtles[0];
+        assert!(skater.finished);
+        assert_eq!(skater.current_energy, 0.0);
+        
+        let mut normal = solo(Terrain::normal());
+        normal.run();
+        assert!(skater.finish_tick.unwrap() < normal.turtles[0].finish_tick.unwrap());
+    }
+    
+    #[test]
+    fn seeded_race_replays_identical_positions() {
+        let run = || {
+            let mut race = race_with(4);
+            race.luck_mode = LuckMode::Continuous;
+            race.run();
+            race.get_positions()
+        };
+        assert_eq!(run(), run());
+        assert_eq!(format!("{:?}", Race::with_seed(500.0, 7).track), format!("{:?}", race_with(0).track));
+    }
+    
+    /// Run a seeded two-turtle race where `fast` crosses well ahead of `slow`
+    fn finish_race(mode: FinishMode) -> Race {
+        let mut race = Race::from_track(vec![Terrain::normal(); 4], DEFAULT_SEGMENT_SIZE);
+        race.finish_mode = mode;
+        for (name, speed) in [("fast", 8.0), ("slow", 4.0)] {
+            let stats = TurtleStats { speed, ..TurtleStats::default() };
+            race.add_turtle(Turtle::with_id(name.to_string(), "00000000".to_string(), stats));
+        }
+        race.run();
+        race
+    }
+    
+    #[test]
+    fn first_across_stops_at_the_first_finisher() {
+        let race = finish_race(FinishMode::FirstAcross);
+        assert!(race.turtles[0].finished);
+        assert!(!race.turtles[1].finished);
+        assert_eq!(race.tick_count, race.turtles[0].finish_tick.unwrap());
+    }
+    
+    #[test]
+    fn all_finish_runs_until_everyone_finishes() {
+        assert_eq!(FinishMode::default(), FinishMode::AllFinish);
+        let race = finish_race(FinishMode::AllFinish);
+        assert!(race.turtles.iter().all(|t| t.finished));
+        assert_eq!(race.tick_count, race.turtles[1].finish_tick.unwrap());
+        assert_eq!(race.results()[1].name, "slow");
+    }
+    
+    #[test]
+    fn run_stages_leaves_a_fresh_race_on_the_restored_track() {
+        let mut race = race_with(3);
+        let track = race.track.clone();
+        let (standings, stages) = race.run_stages(&[200.0, 300.0]);
+        assert_eq!((standings.len(), stages.len()), (3, 2));
+        assert_eq!(race.track_length, 500.0);
+        assert_eq!(race.track.len(), track.len());
+        assert_eq!(race.tick_count, 0);
+        for turtle in &race.turtles {
+            assert_eq!(turtle.race_distance, 0.0);
+            assert!(!turtle.finished);
+            assert_eq!(turtle.current_energy, turtle.stats.max_energy);
+        }
+    }
+    
+    #[test]
+    fn flat_races_are_all_normal_terrain() {
+        let race = Race::flat(500.0, 9, DEFAULT_SEGMENT_SIZE);
+        assert_eq!(race.track.len(), Race::with_seed(500.0, 9).track.len());
+        assert!(race.track.iter().all(|t| t.terrain_type == TerrainType::Normal));
+        assert_eq!(race.seed, 9);
+    }
+    
+    #[test]
+    fn json_round_trip_mid_race_continues_identically() {
+        let mut race = race_with(3);
+        race.luck_mode = LuckMode::Continuous;
+        race.reset();
+        for _ in 0..50 {
+            race.tick();
+        }
+        let mut resumed = Race::from_json(&race.to_json().unwrap()).unwrap();
+        while !race.tick() {}
+        while !resumed.tick() {}
+        assert_eq!(summary(&resumed), summary(&race));
+        assert_eq!(resumed.tick_count, race.tick_count);
+    }
+    
+    #[test]
+    fn setups_without_luck_streams_still_replay() {
+        let mut race = race_with(3);
+        race.luck_mode = LuckMode::Critical;
+        let setup = race.record_setup().unwrap();
+        let mut legacy: serde_json::Value = serde_json::from_slice(&setup).unwrap();
+        for turtle in legacy["turtles"].as_array_mut().unwrap() {
+            turtle.as_object_mut().unwrap().remove("luck_rng");
+        }
+        let legacy = serde_json::to_vec(&legacy).unwrap();
+        let replayed = Race::replay(&legacy).unwrap();
+        assert_eq!(summary(&replayed), summary(&Race::replay(&setup).unwrap()));
+    }
+    
+    #[test]
+    fn empty_race_has_no_winner() {
+        let mut race = race_with(0);
+        assert_eq!(race.run(), None);
+        assert!(race.winner().is_none());
+        assert!(race.results().is_empty());
+    }
+    
+    #[test]
+    fn lone_turtle_wins_outright() {
+        let mut race = race_with(1);
+        assert_eq!(race.run().as_deref(), Some("t0"));
+        assert!(matches!(race.winner(), Some((0, WinReason::Outright))));
+    }
+    
+    #[test]
+    fn zero_length_track_finishes_at_once() {
+        let mut race = Race::with_seed(0.0, 7);
+        assert!(race.track.is_empty());
+        race.add_turtle(Turtle::with_id("t0".to_string(), "00000000".to_string(), TurtleStats::default()));
+        assert_eq!(race.run().as_deref(), Some("t0"));
+        assert_eq!(race.tick_count, 1);
+    }
+    
+    /// Gap between a leader given a 2.0 head start and an identical
+    /// trailer after 10 ticks on flat ground
+    fn gap_after_drafting(draft_distance: Option<f32>) -> f32 {
+        let mut race = Race::flat(1000.0, 1, DEFAULT_SEGMENT_SIZE);
+        race.add_turtle(Turtle::with_id("lead".to_string(), "00000000".to_string(), TurtleStats::default()));
+        race.add_turtle(Turtle::with_id("trail".to_string(), "00000001".to_string(), TurtleStats::default()));
+        race.draft_distance = draft_distance;
+        race.turtles[0].race_distance = 2.0;
+        for _ in 0..10 {
+            race.tick();
+        }
+        race.turtles[0].race_distance - race.turtles[1].race_distance
+    }
+    
+    #[test]
+    fn trailing_turtle_gains_while_drafting() {
+        assert!((gap_after_drafting(None) - 2.0).abs() < 1e-3);
+        let drafted = gap_after_drafting(Some(5.0));
+        assert!(drafted < 2.0 - 0.1, "gap {}", drafted);
+    }
+    
+    /// Distance a lone tireless turtle covers in a default race
+    fn default_race_distance(luck: f32) -> f32 {
+        let mut race = Race::flat(100_000.0, 3, DEFAULT_SEGMENT_SIZE);
+        let stats = TurtleStats { luck, max_energy: 1e9, ..TurtleStats::default() };
+        race.add_turtle(Turtle::with_id("t0".to_string(), "00000000".to_string(), stats));
+        race.reset();
+        for _ in 0..2000 {
+            race.tick();
+        }
+        race.turtles[0].race_distance
+    }
+    
+    #[test]
+    fn luck_counts_in_a_default_race() {
+        assert_eq!(LuckMode::default(), LuckMode::Surge);
+        assert!(default_race_distance(10.0) > default_race_distance(1.0));
+    }
+    
+    #[test]
+    fn validate_length_rejects_infinite_and_non_positive_lengths() {
+        assert!(validate_length("track_length", 500.0).is_ok());
+        for bad in [0.0, -1.0, f32::INFINITY, f32::NAN] {
+            assert!(validate_length("track_length", bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -1,7 +1,20 @@
 //! Terrain types and effects
 
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Extra energy drain per unit of uphill grade
+const UPHILL_DRAIN: f32 = 0.5;
+/// Energy drain saved per unit of downhill grade
+const DOWNHILL_DRAIN_RELIEF: f32 = 0.4;
+/// Speed bonus per unit of downhill grade
+const DOWNHILL_SPEED: f32 = 0.5;
+/// Climb stat assumed when rating inclines
+const AVERAGE_CLIMB: f32 = 5.0;
+
 /// Types of terrain
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TerrainType {
     Normal,
     Water,
@@ -9,27 +22,77 @@ pub enum TerrainType {
     Sand,
     Mud,
     Boost,
+    /// Fast and slippery: turtles cannot stop to rest on it
+    Ice,
+    /// Hill whose effect depends on the segment's `grade`
+    Incline,
 }
 
 impl TerrainType {
+    /// Every terrain type, in declaration order
+    pub const ALL: [TerrainType; 8] = [
+        TerrainType::Normal,
+        TerrainType::Water,
+        TerrainType::Rocks,
+        TerrainType::Sand,
+        TerrainType::Mud,
+        TerrainType::Boost,
+        TerrainType::Ice,
+        TerrainType::Incline,
+    ];
+    
+    /// Lowercase name, as accepted by `from_str`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TerrainType::Normal => "normal",
+            TerrainType::Water => "water",
+            TerrainType::Rocks => "rocks",
+            TerrainType::Sand => "sand",
+            TerrainType::Mud => "mud",
+            TerrainType::Boost => "boost",
+            TerrainType::Ice => "ice",
+            TerrainType::Incline => "incline",
+        }
+    }
+    
+    /// Lenient parse: unknown names fall back to `Normal`
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "water" => TerrainType::Water,
-            "rocks" => TerrainType::Rocks,
-            "sand" => TerrainType::Sand,
-            "mud" => TerrainType::Mud,
-            "boost" => TerrainType::Boost,
-            _ => TerrainType::Normal,
+        Self::parse(s).unwrap_or(TerrainType::Normal)
+    }
+    
+    /// Strict parse of a name from `as_str` (case-insensitive)
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.to_lowercase();
+        Self::ALL.into_iter().find(|t| t.as_str() == s)
+    }
+    
+    /// Whether a turtle can stop and recover energy here
+    pub fn allows_rest(&self) -> bool {
+        !matches!(self, TerrainType::Ice)
+    }
+    
+    /// Direction a resting turtle drifts on this terrain
+    /// Positive carries it forward, negative slips it back
+    pub fn drift_factor(&self) -> f32 {
+        match self {
+            TerrainType::Water | TerrainType::Boost => 1.0,
+            TerrainType::Mud => -0.5,
+            _ => 0.0,
         }
     }
 }
 
 /// Terrain segment with modifiers
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Terrain {
     pub terrain_type: TerrainType,
     pub speed_modifier: f32,
     pub energy_drain: f32,
+    /// Slope of an incline, clamped to [-1, 1] when applied: positive is
+    /// uphill, negative downhill. Ignored by other terrain types
+    #[serde(default)]
+    pub grade: f32,
 }
 
 impl Terrain {
@@ -38,27 +101,82 @@ impl Terrain {
             terrain_type,
             speed_modifier,
             energy_drain,
+            grade: 0.0,
         }
     }
     
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(type_str: &str, speed_modifier: f32, energy_drain: f32) -> Self {
         Self {
             terrain_type: TerrainType::from_str(type_str),
             speed_modifier,
             energy_drain,
+            grade: 0.0,
         }
     }
     
+    /// Set the incline grade (only meaningful for `TerrainType::Incline`)
+    pub fn with_grade(mut self, grade: f32) -> Self {
+        self.grade = grade;
+        self
+    }
+    
+    /// Grade of an incline clamped to [-1, 1]; 0.0 for any other terrain
+    pub fn effective_grade(&self) -> f32 {
+        match self.terrain_type {
+            TerrainType::Incline => self.grade.clamp(-1.0, 1.0),
+            _ => 0.0,
+        }
+    }
+    
+    /// Energy drain multiplier including the incline grade
+    ///
+    /// Uphill costs `1 + 0.5 * grade` times the base drain, downhill
+    /// `1 - 0.4 * |grade|`; flat terrain uses `energy_drain` as-is.
+    pub fn drain_multiplier(&self) -> f32 {
+        let grade = self.effective_grade();
+        let slope = if grade >= 0.0 {
+            1.0 + UPHILL_DRAIN * grade
+        } else {
+            1.0 + DOWNHILL_DRAIN_RELIEF * grade
+        };
+        self.energy_drain * slope
+    }
+    
+    /// Speed multiplier from the incline grade, given the climb stat
+    ///
+    /// Uphill blends toward `climb / 10` with the grade (a grade of 1 is
+    /// exactly `climb / 10`); downhill adds `0.5 * |grade|` regardless of
+    /// stats.
+    pub fn incline_factor(&self, climb: f32) -> f32 {
+        let grade = self.effective_grade();
+        if grade >= 0.0 {
+            1.0 - grade * (1.0 - climb / 10.0)
+        } else {
+            1.0 - DOWNHILL_SPEED * grade
+        }
+    }
+    
+    /// How punishing this segment is: energy drain per unit of speed
+    ///
+    /// `drain_multiplier / (speed_modifier * incline_factor(5))`, i.e.
+    /// inclines are rated for an average climber. Normal terrain is 1.0,
+    /// mud 3.0, rocks ~2.2, boost ~0.5, ice ~0.3.
+    pub fn difficulty(&self) -> f32 {
+        let speed = self.speed_modifier * self.incline_factor(AVERAGE_CLIMB);
+        self.drain_multiplier() / speed.max(0.1)
+    }
+    
     /// Generate a random track of terrain segments
-    pub fn generate_track(length: f32, segment_size: f32) -> Vec<Terrain> {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    pub fn generate_track<R: Rng + ?Sized>(length: f32, segment_size: f32, rng: &mut R) -> Vec<Terrain> {
         let num_segments = (length / segment_size).ceil() as usize;
         
         (0..num_segments).map(|_| {
             let roll: f32 = rng.gen();
-            if roll < 0.6 {
+            if roll < 0.58 {
                 Terrain::normal()
+            } else if roll < 0.6 {
+                Terrain::ice()
             } else if roll < 0.75 {
                 Terrain::water()
             } else if roll < 0.85 {
@@ -73,6 +191,47 @@ impl Terrain {
         }).collect()
     }
     
+    /// Generate a track sampling each segment's type from `weights`
+    ///
+    /// Weights are relative (normalized internally); types not listed never
+    /// appear. Each type uses its standard preset, and inclines get a grade
+    /// drawn uniformly from [-1, 1]. Errors if any weight is negative or
+    /// non-finite, or none is positive.
+    pub fn generate_weighted<R: Rng + ?Sized>(
+        length: f32,
+        segment_size: f32,
+        weights: &[(TerrainType, f32)],
+        rng: &mut R,
+    ) -> Result<Vec<Terrain>, String> {
+        if weights.iter().any(|(_, w)| !w.is_finite() || *w < 0.0) {
+            return Err("Terrain weights must be non-negative".to_string());
+        }
+        let index = WeightedIndex::new(weights.iter().map(|(_, w)| *w))
+            .map_err(|_| "Terrain weights must sum to more than 0".to_string())?;
+        let num_segments = (length / segment_size).ceil() as usize;
+        
+        Ok((0..num_segments).map(|_| {
+            match &weights[index.sample(rng)].0 {
+                TerrainType::Incline => Terrain::incline(rng.gen_range(-1.0..=1.0)),
+                terrain_type => Terrain::preset(terrain_type),
+            }
+        }).collect())
+    }
+    
+    /// Standard segment for a terrain type (inclines are flat)
+    pub fn preset(terrain_type: &TerrainType) -> Self {
+        match terrain_type {
+            TerrainType::Normal => Terrain::normal(),
+            TerrainType::Water => Terrain::water(),
+            TerrainType::Rocks => Terrain::rocks(),
+            TerrainType::Sand => Terrain::sand(),
+            TerrainType::Mud => Terrain::mud(),
+            TerrainType::Boost => Terrain::boost(),
+            TerrainType::Ice => Terrain::ice(),
+            TerrainType::Incline => Terrain::incline(0.0),
+        }
+    }
+    
     pub fn normal() -> Self {
         Self::new(TerrainType::Normal, 1.0, 1.0)
     }
@@ -96,4 +255,72 @@ impl Terrain {
     pub fn boost() -> Self {
         Self::new(TerrainType::Boost, 1.5, 0.8)
     }
+    
+    pub fn ice() -> Self {
+        Self::new(TerrainType::Ice, 1.4, 0.4)
+    }
+    
+    /// Hill with the given grade (positive uphill, negative downhill)
+    pub fn incline(grade: f32) -> Self {
+        Self::new(TerrainType::Incline, 1.0, 1.0).with_grade(grade)
+    }
+}
+
+/// Race-wide weather scaling every segment's modifiers
+///
+/// | Weather | Speed                          | Energy drain |
+/// |---------|--------------------------------|--------------|
+/// | Clear   | x1.0                           | x1.0         |
+/// | Rain    | x0.9, mud x0.72                | x1.1         |
+/// | Heat    | x1.0, water x0.8, ice x0.85    | x1.2         |
+/// | Fog     | x0.95                          | x1.0         |
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    /// Everything gets muddier
+    Rain,
+    /// Drought: water helps less, ice turns slushy, everything is tiring
+    Heat,
+    /// Poor visibility slows everyone a little
+    Fog,
+}
+
+impl Weather {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "clear" => Some(Weather::Clear),
+            "rain" => Some(Weather::Rain),
+            "heat" => Some(Weather::Heat),
+            "fog" => Some(Weather::Fog),
+            _ => None,
+        }
+    }
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Heat => "heat",
+            Weather::Fog => "fog",
+        }
+    }
+    
+    /// A segment as it plays under this weather
+    pub fn apply(&self, terrain: &Terrain) -> Terrain {
+        let (speed, drain) = match (self, terrain.terrain_type) {
+            (Weather::Clear, _) => (1.0, 1.0),
+            (Weather::Rain, TerrainType::Mud) => (0.72, 1.1),
+            (Weather::Rain, _) => (0.9, 1.1),
+            (Weather::Heat, TerrainType::Water) => (0.8, 1.2),
+            (Weather::Heat, TerrainType::Ice) => (0.85, 1.2),
+            (Weather::Heat, _) => (1.0, 1.2),
+            (Weather::Fog, _) => (0.95, 1.0),
+        };
+        Terrain {
+            speed_modifier: terrain.speed_modifier * speed,
+            energy_drain: terrain.energy_drain * drain,
+            ..*terrain
+        }
+    }
 }
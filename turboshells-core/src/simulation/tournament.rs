@@ -0,0 +1,146 @@
+//! Tournaments: single-elimination brackets and round-robin leagues
+
+use rayon::prelude::*;
+use super::race::{FinishMode, Race};
+use super::turtle::Turtle;
+use crate::rng::derive_seed;
+
+/// One head-to-head match; `b` is None for a bye
+#[derive(Clone, Debug)]
+pub struct Match {
+    pub a: usize,
+    pub b: Option<usize>,
+    pub winner: usize,
+}
+
+/// Result of a tournament; turtle references are roster indices
+#[derive(Clone, Debug)]
+pub struct Bracket {
+    pub rounds: Vec<Vec<Match>>,
+    /// None if the round limit was hit before a single turtle remained
+    pub champion: Option<usize>,
+}
+
+/// A turtle's record over a round robin; `turtle` is a roster index
+#[derive(Clone, Debug)]
+pub struct Standing {
+    pub turtle: usize,
+    pub wins: u32,
+    /// Opponents finished ahead of, summed over every race
+    pub points: u32,
+    /// Distance covered summed over every race
+    pub total_distance: f32,
+}
+
+/// Run up to `rounds` single-elimination rounds
+///
+/// Entrants pair off in roster order; an odd one out gets a bye. Matches
+/// within a round run in parallel, each on its own track seeded with
+/// `derive_seed(derive_seed(seed, round), match)`, so a seed fully
+/// determines the bracket. Each match races clones of its two turtles,
+/// which is what lets rayon run them independently.
+pub fn run_tournament(turtles: &[Turtle], track_length: f32, rounds: usize, seed: u64) -> Bracket {
+    let mut alive: Vec<usize> = (0..turtles.len()).collect();
+    let mut bracket = Bracket { rounds: Vec::new(), champion: None };
+    
+    for round in 0..rounds {
+        if alive.len() <= 1 {
+            break;
+        }
+        let round_seed = derive_seed(seed, round as u64);
+        let matches: Vec<Match> = alive
+            .par_chunks(2)
+            .enumerate()
+            .map(|(i, pair)| match *pair {
+                [a, b] => Match { a, b: Some(b), winner: race_pair(turtles, a, b, track_length, derive_seed(round_seed, i as u64)) },
+                _ => Match { a: pair[0], b: None, winner: pair[0] },
+            })
+            .collect();
+        alive = matches.iter().map(|m| m.winner).collect();
+        bracket.rounds.push(matches);
+    }
+    
+    if alive.len() == 1 {
+        bracket.champion = Some(alive[0]);
+    }
+    bracket
+}
+
+/// Play a round robin and return standings, best first
+///
+/// By default every pairing races once head to head (in parallel, match
+/// `i` on a track seeded with `derive_seed(seed, i)`), so points equal
+/// wins. With `single_race` everyone runs one race on a track seeded with
+/// `seed` instead: the winner takes the only win and each turtle scores a
+/// point per opponent placed behind it. Standings sort by points, then
+/// total distance, then roster order.
+pub fn round_robin(turtles: &[Turtle], track_length: f32, single_race: bool, seed: u64) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = (0..turtles.len())
+        .map(|turtle| Standing { turtle, wins: 0, points: 0, total_distance: 0.0 })
+        .collect();
+    
+    if single_race {
+        let mut race = Race::with_seed(track_length, seed);
+        for turtle in turtles {
+            race.add_turtle(turtle.clone());
+        }
+        race.run();
+        if let Some((winner, _)) = race.winner() {
+            standings[winner].wins += 1;
+        }
+        let results = race.results();
+        for placement in &results {
+            let standing = &mut standings[placement.turtle];
+            standing.points = results.iter().filter(|other| other.place > placement.place).count() as u32;
+            standing.total_distance = placement.distance;
+        }
+    } else {
+        let pairings: Vec<(usize, usize)> = (0..turtles.len())
+            .flat_map(|a| (a + 1..turtles.len()).map(move |b| (a, b)))
+            .collect();
+        let matches: Vec<(usize, [f32; 2])> = pairings
+            .par_iter()
+            .enumerate()
+            .map(|(i, &(a, b))| {
+                let race = race_head_to_head(turtles, a, b, track_length, derive_seed(seed, i as u64));
+                let winner = match race.winner().map(|(idx, _)| idx) {
+                    Some(1) => b,
+                    _ => a,
+                };
+                (winner, [race.turtles[0].race_distance, race.turtles[1].race_distance])
+            })
+            .collect();
+        for (&(a, b), (winner, distances)) in pairings.iter().zip(matches) {
+            standings[winner].wins += 1;
+            standings[winner].points += 1;
+            standings[a].total_distance += distances[0];
+            standings[b].total_distance += distances[1];
+        }
+    }
+    
+    standings.sort_by(|x, y| {
+        y.points.cmp(&x.points)
+            .then(y.total_distance.total_cmp(&x.total_distance))
+            .then(x.turtle.cmp(&y.turtle))
+    });
+    standings
+}
+
+/// Race two turtles head to head and return the winner's index
+fn race_pair(turtles: &[Turtle], a: usize, b: usize, track_length: f32, seed: u64) -> usize {
+    let race = race_head_to_head(turtles, a, b, track_length, seed);
+    match race.winner().map(|(idx, _)| idx) {
+        Some(1) => b,
+        _ => a,
+    }
+}
+
+/// Run a first-across race between clones of turtles `a` and `b`
+fn race_head_to_head(turtles: &[Turtle], a: usize, b: usize, track_length: f32, seed: u64) -> Race {
+    let mut race = Race::with_seed(track_length, seed);
+    race.finish_mode = FinishMode::FirstAcross;
+    race.add_turtle(turtles[a].clone());
+    race.add_turtle(turtles[b].clone());
+    race.run();
+    race
+}
@@ -1,109 +1,905 @@
 //! Turtle entity with physics
 
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::rng::{self, derive_seed, SeededRng};
 use crate::types::TurtleStats;
 use super::terrain::{Terrain, TerrainType};
 use uuid::Uuid;
 
-/// Physics constants (matching Python)
-const TERRAIN_DIFFICULTY: f32 = 0.8;
-const RECOVERY_RATE: f32 = 0.1;
-const RECOVERY_THRESHOLD: f32 = 0.5;
+/// Default physics tuning (matching Python)
+pub const DEFAULT_TERRAIN_DIFFICULTY: f32 = 0.8;
+pub const DEFAULT_RECOVERY_RATE: f32 = 0.1;
+pub const DEFAULT_RECOVERY_THRESHOLD: f32 = 0.5;
+pub const DEFAULT_MUD_ENERGY_EXPONENT: f32 = 1.0;
+pub const DEFAULT_BASE_ENERGY_DRAIN: f32 = 0.5;
 
-/// A racing turtle with stats and physics
+fn default_terrain_difficulty() -> f32 {
+    DEFAULT_TERRAIN_DIFFICULTY
+}
+
+fn default_recovery_rate() -> f32 {
+    DEFAULT_RECOVERY_RATE
+}
+
+fn default_recovery_threshold() -> f32 {
+    DEFAULT_RECOVERY_THRESHOLD
+}
+
+fn default_mud_energy_exponent() -> f32 {
+    DEFAULT_MUD_ENERGY_EXPONENT
+}
+
+fn default_base_energy_drain() -> f32 {
+    DEFAULT_BASE_ENERGY_DRAIN
+}
+
+/// Ticks of movement kept for speed projections
+pub const PROJECTION_WINDOW: usize = 10;
+
+/// Luck tuning
+const LUCK_BONUS: f32 = 0.1;
+const CRIT_CHANCE_PER_LUCK: f32 = 0.01;
+const CRIT_MOVE_MULTIPLIER: f32 = 1.5;
+const CRIT_RECOVERY_MULTIPLIER: f32 = 2.0;
+const SURGE_MULTIPLIER: f32 = 1.15;
+
+/// Fatigue gained per unit of distance moved, before the stamina divisor
+const FATIGUE_PER_DISTANCE: f32 = 1e-4;
+/// Stamina points that add one to the fatigue divisor
+const STAMINA_PER_FATIGUE_DIVISOR: f32 = 5.0;
+
+/// Pacing: energy fraction below which a pacing turtle eases off,
+/// `PACING_THRESHOLD_BASE + PACING_THRESHOLD_PER_STAMINA * stamina`
+const PACING_THRESHOLD_BASE: f32 = 0.1;
+const PACING_THRESHOLD_PER_STAMINA: f32 = 0.03;
+/// Pacing effort (speed fraction), `PACING_EFFORT_BASE + PACING_EFFORT_PER_STAMINA * stamina`
+const PACING_EFFORT_BASE: f32 = 0.5;
+const PACING_EFFORT_PER_STAMINA: f32 = 0.03;
+
+/// Size tuning: speed change per unit of size above/below 1.0
+const SIZE_WATER_DRAG: f32 = 0.3;
+const SIZE_ROCK_STABILITY: f32 = 0.2;
+
+/// Length of a turtle id
+pub const ID_LEN: usize = 8;
+
+/// When set, new turtle ids derive from (seed, running counter)
+static ID_SEQUENCE: Mutex<Option<(u64, u64)>> = Mutex::new(None);
+
+/// Make turtle ids deterministic from `seed`, or random again with None
+/// The n-th turtle created after seeding gets the same id on every run
+pub fn set_id_seed(seed: Option<u64>) {
+    *ID_SEQUENCE.lock().unwrap() = seed.map(|seed| (seed, 0));
+}
+
+/// Next id: from the seeded sequence if set, otherwise a random UUID prefix
+fn next_id() -> String {
+    let mut sequence = ID_SEQUENCE.lock().unwrap();
+    match sequence.as_mut() {
+        Some((seed, counter)) => {
+            let id = derive_seed(*seed, *counter);
+            *counter += 1;
+            format!("{:016x}", id)[..ID_LEN].to_string()
+        },
+        None => Uuid::new_v4().to_string()[..ID_LEN].to_string(),
+    }
+}
+
+/// Fit a caller-supplied id to `ID_LEN` characters
+///
+/// Longer ids are truncated, shorter ones right-padded with '0'.
+/// Returns None for an empty id.
+pub fn normalize_id(id: &str) -> Option<String> {
+    if id.is_empty() {
+        return None;
+    }
+    Some(id.chars().chain(std::iter::repeat('0')).take(ID_LEN).collect())
+}
+
+/// How the luck stat influences each tick
+///
+/// - `Surge` (default): each movement tick has a `luck / 100` chance of a
+///   lucky surge that multiplies speed by 1.15.
+/// - `Continuous`: every movement tick speed is scaled by
+///   `1 + 0.1 * (luck / 10) * u` with `u` uniform in [0, 1) — a smooth
+///   bonus of up to +10% at luck 10.
+/// - `Critical`: each tick has a `luck / 100` chance of a critical —
+///   1.5x distance when moving, 2x recovery when resting.
+/// - `Off`: luck has no effect and no rolls are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LuckMode {
+    #[default]
+    Surge,
+    Continuous,
+    Critical,
+    Off,
+}
+
+impl LuckMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "surge" => Some(LuckMode::Surge),
+            "continuous" => Some(LuckMode::Continuous),
+            "critical" => Some(LuckMode::Critical),
+            "off" => Some(LuckMode::Off),
+            _ => None,
+        }
+    }
+}
+
+/// A luck roll that fired during a tick: a critical under
+/// `LuckMode::Critical` or a surge under `LuckMode::Surge`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LuckRoll {
+    #[serde(alias = "Move")]
+    CriticalMove,
+    #[serde(alias = "Recovery")]
+    CriticalRecovery,
+    Surge,
+}
+
+impl LuckRoll {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LuckRoll::CriticalMove => "critical_move",
+            LuckRoll::CriticalRecovery => "critical_recovery",
+            LuckRoll::Surge => "lucky_surge",
+        }
+    }
+}
+
+/// Result of advancing a turtle by one tick
+#[derive(Clone, Copy, Debug)]
+pub struct StepOutcome {
+    pub distance: f32,
+    pub roll: Option<LuckRoll>,
+}
+
+/// Per-turtle tunable physics parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhysicsConfig {
+    /// Distance a resting turtle drifts per tick, scaled by the terrain's
+    /// drift factor (water/boost carry it forward, mud slips it back).
+    /// 0.0 keeps resting turtles stationary.
+    pub resting_drift: f32,
+    /// Let the `size` stat scale terrain effects: larger turtles are
+    /// slower in water but steadier on rocks, smaller ones the reverse
+    pub size_effects: bool,
+    /// Energy regained per point of `recovery` each resting tick, before
+    /// the stamina bonus
+    #[serde(default = "default_recovery_rate")]
+    pub recovery_rate: f32,
+    /// Energy fraction a resting turtle waits for before running again,
+    /// before grit scaling; in (0, 1]
+    #[serde(default = "default_recovery_threshold")]
+    pub recovery_threshold: f32,
+    /// Energy every move costs before terrain difficulty, terrain drain
+    /// and pacing; lower for endurance races, higher for sprints
+    #[serde(default = "default_base_energy_drain")]
+    pub base_energy_drain: f32,
+    /// Scales the base energy drain of every move
+    #[serde(default = "default_terrain_difficulty")]
+    pub terrain_difficulty: f32,
+    /// Curve of mud speed against remaining energy: speed scales with
+    /// `energy_fraction^exponent`. 1.0 is linear; below 1 low-energy
+    /// turtles keep more speed, above 1 they bog down sooner
+    #[serde(default = "default_mud_energy_exponent")]
+    pub mud_energy_exponent: f32,
+    /// Share of last tick's move speed carried into this one, in [0, 1);
+    /// 0.0 switches speed instantly at terrain changes
+    #[serde(default)]
+    pub inertia: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            resting_drift: 0.0,
+            size_effects: false,
+            recovery_rate: DEFAULT_RECOVERY_RATE,
+            recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+            base_energy_drain: DEFAULT_BASE_ENERGY_DRAIN,
+            terrain_difficulty: DEFAULT_TERRAIN_DIFFICULTY,
+            mud_energy_exponent: DEFAULT_MUD_ENERGY_EXPONENT,
+            inertia: 0.0,
+        }
+    }
+}
+
+/// Components of a turtle's move speed for one tick
 #[derive(Clone, Debug)]
+pub struct SpeedBreakdown {
+    pub base_speed: f32,
+    /// Stat bonus for the terrain times the terrain's speed modifier
+    pub terrain_multiplier: f32,
+    /// Size effect on water/rocks (1.0 unless size effects are enabled)
+    pub size_factor: f32,
+    /// Remaining energy fraction raised to the mud energy exponent (only
+    /// applies on mud)
+    pub energy_factor: f32,
+    /// Late-race slowdown from accumulated fatigue: `1 / (1 + fatigue)`
+    pub fatigue_factor: f32,
+    /// Reduced effort while pacing (1.0 when not pacing)
+    pub pacing_factor: f32,
+    pub luck_factor: f32,
+    /// Product of the factors above: the speed inertia eases toward
+    pub target_speed: f32,
+    /// Last tick's move speed, carried in by inertia
+    pub carried_speed: f32,
+    /// Share of `carried_speed` kept this tick
+    pub inertia: f32,
+    /// Distance the turtle would move: `inertia * carried_speed +
+    /// (1 - inertia) * target_speed` (drift only, if resting)
+    pub final_speed: f32,
+}
+
+/// Speed multiplier a terrain applies given the turtle's stats
+pub fn terrain_multiplier(stats: &TurtleStats, terrain: &Terrain) -> f32 {
+    match terrain.terrain_type {
+        TerrainType::Water => (stats.swim / 10.0) * terrain.speed_modifier,
+        TerrainType::Rocks => (stats.climb / 10.0) * terrain.speed_modifier,
+        TerrainType::Sand => (1.0 + stats.recovery / 15.0) * terrain.speed_modifier,
+        TerrainType::Mud => terrain.speed_modifier,
+        TerrainType::Boost => terrain.speed_modifier * 1.2,
+        TerrainType::Incline => terrain.incline_factor(stats.climb) * terrain.speed_modifier,
+        TerrainType::Normal | TerrainType::Ice => terrain.speed_modifier,
+    }
+}
+
+/// Speed multiplier from body size on a terrain
+///
+/// Water: `1 - 0.3 * (size - 1)`; rocks: `1 + 0.2 * (size - 1)`;
+/// other terrain is unaffected. Never drops below 0.1.
+pub fn size_factor(stats: &TurtleStats, terrain: &Terrain) -> f32 {
+    let offset = stats.size - 1.0;
+    let factor = match terrain.terrain_type {
+        TerrainType::Water => 1.0 - SIZE_WATER_DRAG * offset,
+        TerrainType::Rocks => 1.0 + SIZE_ROCK_STABILITY * offset,
+        _ => 1.0,
+    };
+    factor.max(0.1)
+}
+
+/// Equipment effect on one stat: `(base + add) * mult`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatModifier {
+    pub stat: String,
+    pub add: f32,
+    pub mult: f32,
+}
+
+/// Temporary race-time change to one stat, added after equipment
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimedModifier {
+    pub stat: String,
+    pub delta: f32,
+    /// Ticks of effect left, including the next one
+    pub ticks_left: u32,
+}
+
+/// Luck stream for a turtle outside any race
+/// A fixed sub-stream of `rng::DEFAULT_SEED`, so standalone physics is
+/// reproducible; races reseed it per roster slot
+fn fresh_luck_rng() -> SeededRng {
+    rng::seeded(derive_seed(rng::DEFAULT_SEED, 0))
+}
+
+fn keep_equipment_default() -> bool {
+    true
+}
+
+/// A racing turtle with stats and physics
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Turtle {
     pub id: String,
     pub name: String,
     pub stats: TurtleStats,
+    pub physics: PhysicsConfig,
+    /// Per-turtle luck stream; races reseed it from the race seed, and it
+    /// is saved with the turtle so a resumed race draws the same rolls
+    #[serde(default = "fresh_luck_rng")]
+    pub luck_rng: SeededRng,
+    /// Equipment layered over `stats`, applied in order
+    #[serde(default)]
+    pub equipment: Vec<StatModifier>,
+    /// Keep equipment after a race ends; otherwise it is used up
+    #[serde(default = "keep_equipment_default")]
+    pub keep_equipment: bool,
+    /// Buffs and debuffs that wear off after a number of ticks
+    #[serde(default)]
+    pub modifiers: Vec<TimedModifier>,
+    /// Ease off when energy runs low instead of running dry and resting
+    #[serde(default)]
+    pub pacing_enabled: bool,
     
     // Race state
     pub current_energy: f32,
     pub race_distance: f32,
     pub is_resting: bool,
+    /// Accumulated tiredness from distance moved this race
+    #[serde(default)]
+    pub fatigue: f32,
+    /// Move speed of the last tick, carried forward by inertia
+    #[serde(default)]
+    pub last_move_speed: f32,
+    /// Distance moved in each of the last `PROJECTION_WINDOW` race ticks
+    #[serde(default)]
+    pub recent_deltas: VecDeque<f32>,
+    /// Tick each checkpoint was first passed, in checkpoint order; holds
+    /// only the checkpoints reached so far
+    #[serde(default)]
+    pub splits: Vec<u32>,
+    /// Tick on which the turtle first ran out of energy and began resting
+    #[serde(default)]
+    pub first_bonk_tick: Option<u32>,
     pub finished: bool,
+    /// Tick on which the finish line was crossed
+    pub finish_tick: Option<u32>,
+    /// Interpolated crossing time in fractional ticks
+    pub finish_time: Option<f32>,
 }
 
 impl Turtle {
     pub fn new(name: String, stats: TurtleStats) -> Self {
-        let id = Uuid::new_v4().to_string()[..8].to_string();
+        Self::with_id(name, next_id(), stats)
+    }
+    
+    /// Create a turtle with a fixed id (used as given; see `normalize_id`)
+    pub fn with_id(name: String, id: String, stats: TurtleStats) -> Self {
         let current_energy = stats.max_energy;
         
         Self {
             id,
             name,
             stats,
+            physics: PhysicsConfig::default(),
+            luck_rng: fresh_luck_rng(),
+            equipment: Vec::new(),
+            keep_equipment: true,
+            modifiers: Vec::new(),
+            pacing_enabled: false,
             current_energy,
             race_distance: 0.0,
             is_resting: false,
+            fatigue: 0.0,
+            last_move_speed: 0.0,
+            recent_deltas: VecDeque::new(),
+            splits: Vec::new(),
+            first_bonk_tick: None,
             finished: false,
+            finish_tick: None,
+            finish_time: None,
+        }
+    }
+    
+    /// Base stats with equipment, then timed modifiers, applied; this is
+    /// what physics uses
+    pub fn effective_stats(&self) -> TurtleStats {
+        let mut stats = self.stats.clone();
+        for modifier in &self.equipment {
+            if let Some(value) = stats.get_mut(&modifier.stat) {
+                *value = (*value + modifier.add) * modifier.mult;
+            }
         }
+        for modifier in &self.modifiers {
+            if let Some(value) = stats.get_mut(&modifier.stat) {
+                *value += modifier.delta;
+            }
+        }
+        stats
     }
     
-    /// Reset for a new race
+    /// Called by the race once it is over; drops equipment not kept
+    pub fn end_race(&mut self) {
+        if !self.keep_equipment {
+            self.equipment.clear();
+        }
+    }
+    
+    /// Remember one tick's movement, dropping the oldest past the window
+    pub fn record_delta(&mut self, delta: f32) {
+        if self.recent_deltas.len() == PROJECTION_WINDOW {
+            self.recent_deltas.pop_front();
+        }
+        self.recent_deltas.push_back(delta);
+    }
+    
+    /// Mean distance per tick over the recorded window (0.0 if empty)
+    pub fn recent_speed(&self) -> f32 {
+        if self.recent_deltas.is_empty() {
+            return 0.0;
+        }
+        self.recent_deltas.iter().sum::<f32>() / self.recent_deltas.len() as f32
+    }
+    
+    /// Reset for a new race; timed modifiers are cleared first
     pub fn reset_for_race(&mut self) {
-        self.current_energy = self.stats.max_energy;
+        self.modifiers.clear();
+        self.current_energy = self.effective_stats().max_energy;
         self.race_distance = 0.0;
         self.is_resting = false;
+        self.fatigue = 0.0;
+        self.last_move_speed = 0.0;
+        self.recent_deltas.clear();
+        self.splits.clear();
+        self.first_bonk_tick = None;
         self.finished = false;
+        self.finish_tick = None;
+        self.finish_time = None;
+    }
+    
+    /// Start the next leg of a staged race
+    /// Only progress resets; energy, fatigue and resting carry over
+    pub fn reset_for_stage(&mut self) {
+        self.race_distance = 0.0;
+        self.splits.clear();
+        self.first_bonk_tick = None;
+        self.finished = false;
+        self.finish_tick = None;
+        self.finish_time = None;
+    }
+    
+    /// Energy fraction at which this turtle stops resting
+    ///
+    /// Scales the base threshold (`physics.recovery_threshold`, 0.5 by
+    /// default) by grit: `base * (2 - grit / 5)`, so grit 5 keeps the base,
+    /// grit 0 doubles it and grit 10 pushes off almost immediately.
+    /// Clamped to [0.05, 1.0].
+    pub fn recovery_threshold(&self) -> f32 {
+        self.recovery_threshold_for(&self.effective_stats())
+    }
+    
+    /// `recovery_threshold` given this tick's effective stats
+    fn recovery_threshold_for(&self, stats: &TurtleStats) -> f32 {
+        (self.physics.recovery_threshold * (2.0 - stats.grit / 5.0)).clamp(0.05, 1.0)
+    }
+    
+    /// Fraction of full effort this tick; below 1.0 only while pacing
+    ///
+    /// With pacing enabled and energy under `0.1 + 0.03 * stamina` of max,
+    /// the turtle moves at `0.5 + 0.03 * stamina` of its speed (capped at
+    /// 1.0) and drains energy at the square of that, so it stretches its
+    /// last reserves instead of stopping to rest. High stamina paces
+    /// earlier and gives up less speed.
+    pub fn pacing_effort(&self) -> f32 {
+        self.pacing_effort_for(&self.effective_stats())
+    }
+    
+    /// `pacing_effort` given this tick's effective stats
+    fn pacing_effort_for(&self, stats: &TurtleStats) -> f32 {
+        if !self.pacing_enabled {
+            return 1.0;
+        }
+        let threshold = PACING_THRESHOLD_BASE + PACING_THRESHOLD_PER_STAMINA * stats.stamina;
+        if self.current_energy >= stats.max_energy * threshold {
+            return 1.0;
+        }
+        (PACING_EFFORT_BASE + PACING_EFFORT_PER_STAMINA * stats.stamina).clamp(0.1, 1.0)
+    }
+    
+    /// Explain the current move speed on a terrain without changing state
+    /// Mirrors the movement step of `update_physics`
+    pub fn speed_breakdown(&self, terrain: &Terrain) -> SpeedBreakdown {
+        self.speed_breakdown_for(terrain, &self.effective_stats())
+    }
+    
+    /// `speed_breakdown` given this tick's effective stats
+    fn speed_breakdown_for(&self, terrain: &Terrain, stats: &TurtleStats) -> SpeedBreakdown {
+        let base_speed = stats.speed;
+        let terrain_multiplier = terrain_multiplier(stats, terrain);
+        let size_factor = if self.physics.size_effects {
+            size_factor(stats, terrain)
+        } else {
+            1.0
+        };
+        let energy_factor = match terrain.terrain_type {
+            TerrainType::Mud => (self.current_energy / stats.max_energy)
+                .max(0.0)
+                .powf(self.physics.mud_energy_exponent),
+            _ => 1.0,
+        };
+        let fatigue_factor = 1.0 / (1.0 + self.fatigue);
+        let pacing_factor = self.pacing_effort_for(stats);
+        // Luck is rolled per tick in `step`, so it is not part of the breakdown
+        let luck_factor = 1.0;
+        
+        let carried_speed = self.last_move_speed;
+        let inertia = self.physics.inertia;
+        let (target_speed, final_speed) = if self.finished {
+            (0.0, 0.0)
+        } else if self.is_resting && terrain.terrain_type.allows_rest() {
+            let drift = self.physics.resting_drift * terrain.terrain_type.drift_factor();
+            (drift, drift)
+        } else {
+            let target = base_speed * terrain_multiplier * size_factor * energy_factor * fatigue_factor * pacing_factor * luck_factor;
+            (target, inertia * carried_speed + (1.0 - inertia) * target)
+        };
+        
+        SpeedBreakdown {
+            base_speed,
+            terrain_multiplier,
+            size_factor,
+            energy_factor,
+            fatigue_factor,
+            pacing_factor,
+            luck_factor,
+            target_speed,
+            carried_speed,
+            inertia,
+            final_speed,
+        }
     }
     
     /// Update physics for one tick
     /// Returns distance moved
     pub fn update_physics(&mut self, terrain: &Terrain) -> f32 {
+        self.step(terrain, LuckMode::default()).distance
+    }
+    
+    /// Advance one tick under the given luck mode
+    /// Timed modifiers apply to this tick, then count down one tick; they
+    /// count down while resting too, so a buff lasts a fixed span of race
+    /// time rather than of movement
+    pub fn step(&mut self, terrain: &Terrain, luck_mode: LuckMode) -> StepOutcome {
         if self.finished {
-            return 0.0;
+            return StepOutcome { distance: 0.0, roll: None };
+        }
+        // Equipment and modifiers are resolved once for the whole tick
+        let stats = self.effective_stats();
+        let outcome = self.advance(terrain, luck_mode, &stats);
+        for modifier in &mut self.modifiers {
+            modifier.ticks_left -= 1;
+        }
+        self.modifiers.retain(|modifier| modifier.ticks_left > 0);
+        outcome
+    }
+    
+    /// One tick of recovery or movement and energy drain
+    fn advance(&mut self, terrain: &Terrain, luck_mode: LuckMode, stats: &TurtleStats) -> StepOutcome {
+        // No traction to stop on ice: a resting turtle skids on instead
+        let can_rest = terrain.terrain_type.allows_rest();
+        if !can_rest {
+            self.is_resting = false;
         }
         
+        let crit_chance = (stats.luck * CRIT_CHANCE_PER_LUCK).clamp(0.0, 1.0);
+        let critical = luck_mode == LuckMode::Critical && self.luck_rng.gen::<f32>() < crit_chance;
+        
         // 1. RECOVERY LOGIC
         if self.is_resting {
-            let stamina_bonus = self.stats.stamina / 20.0;
-            let recovery_rate = RECOVERY_RATE * (1.0 + stamina_bonus);
-            self.current_energy += self.stats.recovery * recovery_rate;
+            let stamina_bonus = stats.stamina / 20.0;
+            let mut recovery_rate = self.physics.recovery_rate * (1.0 + stamina_bonus);
+            if critical {
+                recovery_rate *= CRIT_RECOVERY_MULTIPLIER;
+            }
+            self.current_energy += stats.recovery * recovery_rate;
             
-            if self.current_energy >= self.stats.max_energy * RECOVERY_THRESHOLD {
+            if self.current_energy >= stats.max_energy * self.recovery_threshold_for(stats) {
                 self.is_resting = false;
             }
-            return 0.0;
+            // Stopping to rest sheds any carried speed
+            self.last_move_speed = 0.0;
+            return StepOutcome {
+                distance: self.physics.resting_drift * terrain.terrain_type.drift_factor(),
+                roll: critical.then_some(LuckRoll::CriticalRecovery),
+            };
         }
         
         // 2. MOVEMENT LOGIC
-        let mut move_speed = self.stats.speed;
-        
-        match terrain.terrain_type {
-            TerrainType::Water => {
-                let swim_bonus = self.stats.swim / 10.0;
-                move_speed *= swim_bonus * terrain.speed_modifier;
+        let mut move_speed = self.speed_breakdown_for(terrain, stats).target_speed;
+        let mut roll = critical.then_some(LuckRoll::CriticalMove);
+        match luck_mode {
+            LuckMode::Surge => {
+                if self.luck_rng.gen::<f32>() < crit_chance {
+                    move_speed *= SURGE_MULTIPLIER;
+                    roll = Some(LuckRoll::Surge);
+                }
             },
-            TerrainType::Rocks => {
-                let climb_bonus = self.stats.climb / 10.0;
-                move_speed *= climb_bonus * terrain.speed_modifier;
+            LuckMode::Continuous => {
+                let u: f32 = self.luck_rng.gen();
+                move_speed *= 1.0 + LUCK_BONUS * (stats.luck / 10.0) * u;
             },
-            TerrainType::Sand => {
-                let recovery_bonus = self.stats.recovery / 15.0;
-                move_speed *= (1.0 + recovery_bonus) * terrain.speed_modifier;
-            },
-            TerrainType::Mud => {
-                let energy_factor = self.current_energy / self.stats.max_energy;
-                move_speed *= energy_factor * terrain.speed_modifier;
-            },
-            TerrainType::Boost => {
-                move_speed *= terrain.speed_modifier * 1.2;
-            },
-            TerrainType::Normal => {
-                move_speed *= terrain.speed_modifier;
+            LuckMode::Critical if critical => {
+                move_speed *= CRIT_MOVE_MULTIPLIER;
             },
+            LuckMode::Critical | LuckMode::Off => {},
         }
         
+        // Inertia blends in last tick's speed, easing terrain transitions
+        let inertia = self.physics.inertia;
+        move_speed = inertia * self.last_move_speed + (1.0 - inertia) * move_speed;
+        self.last_move_speed = move_speed;
+        
+        // Fatigue builds with distance; stamina flattens the slope
+        self.fatigue += move_speed * FATIGUE_PER_DISTANCE / (1.0 + stats.stamina / STAMINA_PER_FATIGUE_DIVISOR);
+        
         // 3. ENERGY DRAIN
-        let base_drain = 0.5 * TERRAIN_DIFFICULTY;
-        let actual_drain = base_drain * terrain.energy_drain;
+        let base_drain = self.physics.base_energy_drain * self.physics.terrain_difficulty;
+        let effort = self.pacing_effort_for(stats);
+        let actual_drain = base_drain * terrain.drain_multiplier() * effort * effort;
         self.current_energy -= actual_drain;
         
         if self.current_energy <= 0.0 {
             self.current_energy = 0.0;
-            self.is_resting = true;
+            self.is_resting = can_rest;
+        }
+        
+        StepOutcome {
+            distance: move_speed,
+            roll,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn turtle() -> Turtle {
+        Turtle::with_id("test".to_string(), "00000000".to_string(), TurtleStats::default())
+    }
+    
+    fn resting(drift: f32) -> Turtle {
+        let mut turtle = turtle();
+        turtle.physics.resting_drift = drift;
+        turtle.current_energy = 0.0;
+        turtle.is_resting = true;
+        turtle
+    }
+    
+    #[test]
+    fn resting_turtle_drifts_on_boost_but_not_normal_ground() {
+        assert!(resting(0.5).update_physics(&Terrain::boost()) > 0.0);
+        assert_eq!(resting(0.5).update_physics(&Terrain::normal()), 0.0);
+        assert!(resting(0.5).update_physics(&Terrain::mud()) < 0.0);
+        // The default keeps resting turtles still everywhere
+        assert_eq!(resting(0.0).update_physics(&Terrain::boost()), 0.0);
+    }
+    
+    #[test]
+    fn speed_breakdown_pins_known_inputs() {
+        let water = turtle().speed_breakdown(&Terrain::new(TerrainType::Water, 0.8, 1.0));
+        assert_eq!(water.base_speed, 5.0);
+        assert!((water.terrain_multiplier - 0.4).abs() < 1e-6);
+        assert!((water.final_speed - 2.0).abs() < 1e-6);
+        
+        let mut tired = turtle();
+        tired.current_energy = 50.0;
+        let mud = tired.speed_breakdown(&Terrain::new(TerrainType::Mud, 0.5, 1.0));
+        assert_eq!(mud.energy_factor, 0.5);
+        assert!((mud.final_speed - 1.25).abs() < 1e-6);
+        
+        let mut done = turtle();
+        done.finished = true;
+        assert_eq!(done.speed_breakdown(&Terrain::normal()).final_speed, 0.0);
+    }
+    
+    /// Step a tireless turtle `ticks` times, returning (distance, luck events)
+    fn run(luck: f32, mode: LuckMode, ticks: usize) -> (f32, usize) {
+        let stats = TurtleStats { luck, max_energy: 1e9, ..TurtleStats::default() };
+        let mut turtle = Turtle::with_id("test".to_string(), "00000000".to_string(), stats);
+        turtle.luck_rng = rng::seeded(5);
+        let mut distance = 0.0;
+        let mut events = 0;
+        for _ in 0..ticks {
+            let outcome = turtle.step(&Terrain::normal(), mode);
+            distance += outcome.distance;
+            events += outcome.roll.is_some() as usize;
+        }
+        (distance, events)
+    }
+    
+    #[test]
+    fn high_luck_crits_more_under_critical() {
+        let (_, low) = run(1.0, LuckMode::Critical, 5000);
+        let (_, high) = run(10.0, LuckMode::Critical, 5000);
+        // Expected about 50 and 500 crits
+        assert!(high > 3 * low, "luck 1: {}, luck 10: {}", low, high);
+        assert!((400..600).contains(&high), "{}", high);
+    }
+    
+    #[test]
+    fn higher_luck_travels_further() {
+        for mode in [LuckMode::Surge, LuckMode::Continuous, LuckMode::Critical] {
+            let (low, _) = run(1.0, mode, 2000);
+            let (high, _) = run(10.0, mode, 2000);
+            assert!(high > low, "{:?}: luck 1 {}, luck 10 {}", mode, low, high);
+        }
+    }
+    
+    #[test]
+    fn luck_off_ignores_the_stat() {
+        let (low, low_events) = run(1.0, LuckMode::Off, 1000);
+        let (high, high_events) = run(10.0, LuckMode::Off, 1000);
+        assert_eq!((low_events, high_events), (0, 0));
+        assert_eq!(low, high);
+    }
+    
+    #[test]
+    fn standalone_luck_stream_is_deterministic() {
+        let mut a = turtle();
+        let mut b = turtle();
+        assert_eq!(a.luck_rng.gen::<u64>(), b.luck_rng.gen::<u64>());
+    }
+    
+    fn sized(size: f32) -> Turtle {
+        let mut turtle = Turtle::with_id("test".to_string(), "00000000".to_string(), TurtleStats { size, ..TurtleStats::default() });
+        turtle.physics.size_effects = true;
+        turtle
+    }
+    
+    #[test]
+    fn large_turtles_are_slower_in_water_and_faster_on_rocks() {
+        let water = Terrain::water();
+        let rocks = Terrain::rocks();
+        assert!(sized(1.5).update_physics(&water) < sized(0.5).update_physics(&water));
+        assert!(sized(1.5).update_physics(&rocks) > sized(0.5).update_physics(&rocks));
+        
+        // Size only matters once size effects are switched on
+        let mut large = sized(1.5);
+        large.physics.size_effects = false;
+        assert_eq!(large.update_physics(&water), turtle().update_physics(&water));
+    }
+    
+    fn gritty(grit: f32) -> Turtle {
+        Turtle::with_id("test".to_string(), "00000000".to_string(), TurtleStats { grit, ..TurtleStats::default() })
+    }
+    
+    /// Energy at which a drained turtle stops resting
+    fn rest_exit_energy(mut turtle: Turtle) -> f32 {
+        turtle.current_energy = 0.0;
+        turtle.is_resting = true;
+        while turtle.is_resting {
+            turtle.update_physics(&Terrain::normal());
         }
+        turtle.current_energy
+    }
+    
+    /// Ticks to cover `distance` on normal ground
+    fn ticks_to(mut turtle: Turtle, distance: f32) -> u32 {
+        let mut ticks = 0;
+        while turtle.race_distance < distance {
+            turtle.race_distance += turtle.update_physics(&Terrain::normal());
+            ticks += 1;
+        }
+        ticks
+    }
+    
+    #[test]
+    fn grit_sets_rest_exit_energy_and_changes_finish_time() {
+        let tenacious = rest_exit_energy(gritty(9.0));
+        let cautious = rest_exit_energy(gritty(1.0));
+        assert!(tenacious < cautious, "grit 9 left at {}, grit 1 at {}", tenacious, cautious);
+        assert!(cautious >= 90.0 && tenacious <= 25.0);
+        
+        assert_ne!(ticks_to(gritty(9.0), 3000.0), ticks_to(gritty(1.0), 3000.0));
+    }
+    
+    #[test]
+    fn speed_equipment_finishes_faster_until_removed() {
+        let base = ticks_to(turtle(), 2000.0);
+        
+        let mut equipped = turtle();
+        equipped.equipment.push(StatModifier { stat: "speed".to_string(), add: 1.0, mult: 1.2 });
+        assert!((equipped.effective_stats().speed - 7.2).abs() < 1e-5);
+        assert!(ticks_to(equipped.clone(), 2000.0) < base);
         
-        move_speed
+        equipped.equipment.clear();
+        assert_eq!(ticks_to(equipped, 2000.0), base);
+    }
+    
+    /// Ratio of the last tick's speed to the first over `ticks` tireless ticks
+    fn speed_retained(stamina: f32, ticks: usize) -> f32 {
+        let stats = TurtleStats { stamina, max_energy: 1e9, ..TurtleStats::default() };
+        let mut turtle = Turtle::with_id("test".to_string(), "00000000".to_string(), stats);
+        let first = turtle.update_physics(&Terrain::normal());
+        let mut last = first;
+        for _ in 1..ticks {
+            last = turtle.update_physics(&Terrain::normal());
+        }
+        last / first
+    }
+    
+    #[test]
+    fn low_stamina_slows_more_over_a_long_race() {
+        let low = speed_retained(1.0, 1000);
+        let high = speed_retained(10.0, 1000);
+        assert!(low < 1.0 && high < 1.0, "low {}, high {}", low, high);
+        assert!(low < high, "low {}, high {}", low, high);
+    }
+    
+    /// Resting ticks over a run on normal terrain
+    fn resting_ticks(pacing: bool, ticks: usize) -> usize {
+        let mut turtle = turtle();
+        turtle.pacing_enabled = pacing;
+        (0..ticks)
+            .filter(|_| {
+                turtle.update_physics(&Terrain::normal());
+                turtle.is_resting
+            })
+            .count()
+    }
+    
+    #[test]
+    fn pacing_spends_fewer_ticks_fully_resting() {
+        let flat_out = resting_ticks(false, 2000);
+        let paced = resting_ticks(true, 2000);
+        assert!(flat_out > 0);
+        assert!(paced < flat_out, "paced {}, flat out {}", paced, flat_out);
+    }
+    
+    /// Distance covered over `ticks` of mud with the given energy exponent
+    fn mud_distance(exponent: f32, ticks: usize) -> f32 {
+        let mut turtle = turtle();
+        turtle.physics.mud_energy_exponent = exponent;
+        (0..ticks).map(|_| turtle.update_physics(&Terrain::mud())).sum()
+    }
+    
+    #[test]
+    fn steeper_mud_exponents_cover_less_ground() {
+        let gentle = mud_distance(0.5, 300);
+        let linear = mud_distance(1.0, 300);
+        let steep = mud_distance(2.0, 300);
+        assert!(gentle > linear && linear > steep, "0.5: {}, 1.0: {}, 2.0: {}", gentle, linear, steep);
+    }
+    
+    #[test]
+    fn speed_breakdown_includes_carried_speed() {
+        let mut turtle = turtle();
+        turtle.physics.inertia = 0.5;
+        turtle.update_physics(&Terrain::normal());
+        let carried = turtle.last_move_speed;
+        
+        let mut probe = turtle.clone();
+        let breakdown = turtle.speed_breakdown(&Terrain::water());
+        assert_eq!(breakdown.carried_speed, carried);
+        assert_eq!(breakdown.inertia, 0.5);
+        assert!((breakdown.final_speed - (0.5 * carried + 0.5 * breakdown.target_speed)).abs() < 1e-6);
+        // The breakdown predicts the next tick's move exactly
+        assert!((probe.update_physics(&Terrain::water()) - breakdown.final_speed).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn timed_buff_expires_after_its_duration() {
+        let mut turtle = turtle();
+        turtle.modifiers.push(TimedModifier { stat: "speed".to_string(), delta: 2.0, ticks_left: 3 });
+        let base = turtle.stats.speed;
+        for _ in 0..3 {
+            assert_eq!(turtle.effective_stats().speed, base + 2.0);
+            turtle.update_physics(&Terrain::normal());
+        }
+        assert!(turtle.modifiers.is_empty());
+        assert_eq!(turtle.effective_stats().speed, base);
+    }
+    
+    #[test]
+    fn timed_buff_counts_down_while_resting() {
+        let mut turtle = resting(0.0);
+        turtle.modifiers.push(TimedModifier { stat: "speed".to_string(), delta: 2.0, ticks_left: 2 });
+        turtle.update_physics(&Terrain::normal());
+        turtle.update_physics(&Terrain::normal());
+        assert!(turtle.is_resting);
+        assert!(turtle.modifiers.is_empty());
+    }
+    
+    /// Distance covered on normal terrain before the first rest
+    fn distance_before_rest(base_energy_drain: f32) -> f32 {
+        let mut turtle = turtle();
+        turtle.physics.base_energy_drain = base_energy_drain;
+        let mut distance = 0.0;
+        while !turtle.is_resting {
+            distance += turtle.update_physics(&Terrain::normal());
+        }
+        distance
+    }
+    
+    #[test]
+    fn halving_the_drain_runs_further_before_resting() {
+        let full = distance_before_rest(DEFAULT_BASE_ENERGY_DRAIN);
+        let half = distance_before_rest(DEFAULT_BASE_ENERGY_DRAIN / 2.0);
+        assert!(half > 1.5 * full, "full drain {}, half drain {}", full, half);
     }
 }
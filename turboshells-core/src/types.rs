@@ -1,7 +1,61 @@
 //! Shared types for TurboShells Core
 
-use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
+use crate::genetics::GeneDefinition;
+
+/// Color vision deficiency simulated by `Rgb::simulate_cvd`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CvdMode {
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl CvdMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "deuteranopia" => Some(CvdMode::Deuteranopia),
+            "protanopia" => Some(CvdMode::Protanopia),
+            "tritanopia" => Some(CvdMode::Tritanopia),
+            _ => None,
+        }
+    }
+    
+    /// Machado et al. (2009) simulation matrix at full severity, applied
+    /// to linear RGB
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            CvdMode::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            CvdMode::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            CvdMode::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// sRGB channel (0-255) to linear light (0-1)
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Linear light to an sRGB channel, clamped to [0, 255]
+fn linear_to_srgb(c: f32) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
 
 /// RGB color representation
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -26,6 +80,28 @@ impl Rgb {
         (self.r, self.g, self.b)
     }
     
+    /// Lowercase `#rrggbb` form
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+    
+    /// Parse `#rrggbb` or shorthand `#rgb` (the `#` is optional)
+    pub fn from_hex(s: &str) -> Result<Rgb, String> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("'{}' contains non-hex characters", s));
+        }
+        let channel = |hex: &str| u8::from_str_radix(hex, 16).unwrap();
+        match digits.len() {
+            3 => {
+                let nibble = |i: usize| channel(&digits[i..i + 1]) * 17;
+                Ok(Rgb::new(nibble(0), nibble(1), nibble(2)))
+            },
+            6 => Ok(Rgb::new(channel(&digits[0..2]), channel(&digits[2..4]), channel(&digits[4..6]))),
+            _ => Err(format!("'{}' must have 3 or 6 hex digits", s)),
+        }
+    }
+    
     /// Calculate Euclidean distance between two colors
     pub fn distance(&self, other: &Rgb) -> f32 {
         let dr = (self.r as f32) - (other.r as f32);
@@ -43,6 +119,174 @@ impl Rgb {
             b: ((self.b as f32) * (1.0 - bias) + (other.b as f32) * bias) as u8,
         }
     }
+    
+    /// Blend in HSL space (0.0 = self, 1.0 = other)
+    ///
+    /// Hue moves along the shorter arc (wrapping through 0°/360°), while
+    /// saturation and lightness interpolate linearly. A gray side has no
+    /// meaningful hue, so the other color's hue is kept unchanged.
+    pub fn blend_hsl(&self, other: &Rgb, bias: f32) -> Rgb {
+        const GRAY_SATURATION: f32 = 0.02;
+        let bias = bias.clamp(0.0, 1.0);
+        let (h1, s1, l1) = self.to_hsl();
+        let (h2, s2, l2) = other.to_hsl();
+        
+        let hue = match (s1 < GRAY_SATURATION, s2 < GRAY_SATURATION) {
+            (true, true) => 0.0,
+            (true, false) => h2,
+            (false, true) => h1,
+            (false, false) => {
+                let delta = (h2 - h1 + 540.0).rem_euclid(360.0) - 180.0;
+                h1 + delta * bias
+            },
+        };
+        let saturation = s1 + (s2 - s1) * bias;
+        let lightness = l1 + (l2 - l1) * bias;
+        Rgb::from_hsl(hue, saturation, lightness)
+    }
+    
+    /// Convert to HSL: hue in degrees [0, 360), saturation and lightness in [0, 1]
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        
+        if delta == 0.0 {
+            return (0.0, 0.0, l);
+        }
+        
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            60.0 * (((g - b) / delta).rem_euclid(6.0))
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        
+        (h.rem_euclid(360.0), s.clamp(0.0, 1.0), l)
+    }
+    
+    /// Create from HSL (hue in degrees, saturation and lightness in [0, 1])
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let h = h.rem_euclid(360.0);
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+        let m = l - c / 2.0;
+        
+        let (r, g, b) = match (h / 60.0) as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        
+        let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Rgb { r: to_u8(r), g: to_u8(g), b: to_u8(b) }
+    }
+    
+    /// Shift warmth, then brightness; both in [-1, 1] (clamped)
+    ///
+    /// `warmth` adds `64 * warmth` to red and subtracts it from blue
+    /// (negative cools). `brightness` then moves each channel toward white
+    /// (positive) or black (negative) by that fraction, so +1 is white and
+    /// -1 is black. Channels clamp to [0, 255].
+    pub fn adjust(&self, brightness: f32, warmth: f32) -> Rgb {
+        const WARMTH_SHIFT: f32 = 64.0;
+        let brightness = brightness.clamp(-1.0, 1.0);
+        let shift = warmth.clamp(-1.0, 1.0) * WARMTH_SHIFT;
+        let channel = |c: u8, shift: f32| {
+            let c = (c as f32 + shift).clamp(0.0, 255.0);
+            let c = if brightness >= 0.0 {
+                c + (255.0 - c) * brightness
+            } else {
+                c * (1.0 + brightness)
+            };
+            c.round().clamp(0.0, 255.0) as u8
+        };
+        Rgb {
+            r: channel(self.r, shift),
+            g: channel(self.g, 0.0),
+            b: channel(self.b, -shift),
+        }
+    }
+    
+    /// How this color looks to someone with the given color vision deficiency
+    ///
+    /// Linearizes the sRGB channels, applies the Machado et al. (2009)
+    /// full-severity matrix for `mode` and converts back, clamping each
+    /// channel. Black, white and grays are unchanged.
+    pub fn simulate_cvd(&self, mode: CvdMode) -> Rgb {
+        let linear = [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b)];
+        let row = |m: [f32; 3]| linear_to_srgb(m[0] * linear[0] + m[1] * linear[1] + m[2] * linear[2]);
+        let [r, g, b] = mode.matrix();
+        Rgb { r: row(r), g: row(g), b: row(b) }
+    }
+    
+    /// WCAG relative luminance (0.0 = black, 1.0 = white)
+    pub fn relative_luminance(&self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+    
+    /// WCAG contrast ratio between two colors (1.0 to 21.0)
+    pub fn contrast_ratio(&self, other: &Rgb) -> f32 {
+        let l1 = self.relative_luminance();
+        let l2 = other.relative_luminance();
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+    
+    /// Adjust lightness (keeping hue and saturation) until the contrast
+    /// against `other` reaches `min_ratio`
+    ///
+    /// Tries both lightening and darkening and keeps whichever needs the
+    /// smaller lightness change. If neither direction can reach the target,
+    /// returns the extreme with the best achievable contrast.
+    pub fn with_min_contrast(&self, other: &Rgb, min_ratio: f32) -> Rgb {
+        if self.contrast_ratio(other) >= min_ratio {
+            return *self;
+        }
+        
+        let (h, s, l) = self.to_hsl();
+        let search = |target_l: f32| -> Option<(f32, Rgb)> {
+            let extreme = Rgb::from_hsl(h, s, target_l);
+            if extreme.contrast_ratio(other) < min_ratio {
+                return None;
+            }
+            // Bisect between the current lightness (fails) and the extreme (passes)
+            let (mut lo, mut hi) = (l, target_l);
+            for _ in 0..24 {
+                let mid = (lo + hi) / 2.0;
+                if Rgb::from_hsl(h, s, mid).contrast_ratio(other) >= min_ratio {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+            Some(((hi - l).abs(), Rgb::from_hsl(h, s, hi)))
+        };
+        
+        match (search(1.0), search(0.0)) {
+            (Some(up), Some(down)) => if up.0 <= down.0 { up.1 } else { down.1 },
+            (Some((_, c)), None) | (None, Some((_, c))) => c,
+            (None, None) => {
+                let white = Rgb::from_hsl(h, s, 1.0);
+                let black = Rgb::from_hsl(h, s, 0.0);
+                if white.contrast_ratio(other) >= black.contrast_ratio(other) { white } else { black }
+            },
+        }
+    }
 }
 
 impl Default for Rgb {
@@ -61,6 +305,84 @@ pub struct TurtleStats {
     pub climb: f32,
     pub stamina: f32,
     pub luck: f32,
+    /// Body size scale (1.0 = average), typically from `shell_size_modifier`
+    #[serde(default = "default_size")]
+    pub size: f32,
+    /// Tenacity (0-10): high grit resumes racing from rest at lower energy
+    #[serde(default = "default_grit")]
+    pub grit: f32,
+}
+
+impl TurtleStats {
+    /// Look up a stat by name
+    /// "energy" is an alias of "max_energy", the key `PyTurtle` and
+    /// `derive_stats` use
+    pub fn get(&self, name: &str) -> Option<f32> {
+        match name {
+            "speed" => Some(self.speed),
+            "max_energy" | "energy" => Some(self.max_energy),
+            "recovery" => Some(self.recovery),
+            "swim" => Some(self.swim),
+            "climb" => Some(self.climb),
+            "stamina" => Some(self.stamina),
+            "luck" => Some(self.luck),
+            "size" => Some(self.size),
+            "grit" => Some(self.grit),
+            _ => None,
+        }
+    }
+    
+    /// Overall power score, roughly 0-100
+    ///
+    /// Each stat is scaled to 0-1 over its usual range (1-10; max_energy
+    /// 50-150, grit 0-10) and clamped, then weighted: speed 3, max_energy
+    /// 2, recovery 1.5, swim, climb and stamina 1 each, luck and grit 0.5
+    /// each. Size is a trade-off rather than a power and is not counted.
+    /// Raising any one stat never lowers the rating.
+    pub fn rating(&self) -> f32 {
+        const WEIGHTS: [(&str, f32, f32, f32); 8] = [
+            ("speed", 3.0, 1.0, 10.0),
+            ("max_energy", 2.0, 50.0, 150.0),
+            ("recovery", 1.5, 1.0, 10.0),
+            ("swim", 1.0, 1.0, 10.0),
+            ("climb", 1.0, 1.0, 10.0),
+            ("stamina", 1.0, 1.0, 10.0),
+            ("luck", 0.5, 1.0, 10.0),
+            ("grit", 0.5, 0.0, 10.0),
+        ];
+        let total: f32 = WEIGHTS.iter().map(|(_, weight, _, _)| weight).sum();
+        let score: f32 = WEIGHTS.iter()
+            .map(|&(name, weight, min, max)| {
+                let value = self.get(name).unwrap_or(min);
+                weight * ((value - min) / (max - min)).clamp(0.0, 1.0)
+            })
+            .sum();
+        100.0 * score / total
+    }
+    
+    /// Mutable access to a stat by name, accepting the same names as `get`
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut f32> {
+        match name {
+            "speed" => Some(&mut self.speed),
+            "max_energy" | "energy" => Some(&mut self.max_energy),
+            "recovery" => Some(&mut self.recovery),
+            "swim" => Some(&mut self.swim),
+            "climb" => Some(&mut self.climb),
+            "stamina" => Some(&mut self.stamina),
+            "luck" => Some(&mut self.luck),
+            "size" => Some(&mut self.size),
+            "grit" => Some(&mut self.grit),
+            _ => None,
+        }
+    }
+}
+
+fn default_size() -> f32 {
+    1.0
+}
+
+fn default_grit() -> f32 {
+    5.0
 }
 
 impl Default for TurtleStats {
@@ -73,12 +395,14 @@ impl Default for TurtleStats {
             climb: 5.0,
             stamina: 3.0,
             luck: 3.0,
+            size: 1.0,
+            grit: 5.0,
         }
     }
 }
 
 /// Gene value types
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GeneValue {
     Rgb(Rgb),
     Discrete(String),
@@ -106,4 +430,99 @@ impl GeneValue {
             _ => None,
         }
     }
+    
+    /// Distance to another value of the same gene, from 0.0 (identical)
+    /// to 1.0 (opposite ends of the gene's range)
+    ///
+    /// rgb: euclidean distance over the largest possible one; continuous:
+    /// absolute difference over the range; discrete: 0 if equal, else 1.
+    /// None if either value does not match the gene's type, or a
+    /// continuous gene has no range.
+    pub fn normalized_distance(&self, other: &GeneValue, def: &GeneDefinition) -> Option<f32> {
+        match (self, other, def.gene_type.as_str()) {
+            (GeneValue::Rgb(c1), GeneValue::Rgb(c2), "rgb") => {
+                let max_dist = (255.0_f32.powi(2) * 3.0).sqrt();
+                Some(c1.distance(c2) / max_dist)
+            },
+            (GeneValue::Continuous(f1), GeneValue::Continuous(f2), "continuous") => {
+                let (min, max) = def.continuous_range?;
+                Some(((f1 - f2).abs() / (max - min)).clamp(0.0, 1.0))
+            },
+            (GeneValue::Discrete(s1), GeneValue::Discrete(s2), "discrete") => {
+                Some(if s1 == s2 { 0.0 } else { 1.0 })
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn with_min_contrast_fixes_a_failing_pair() {
+        let fg = Rgb::new(120, 140, 90);
+        let bg = Rgb::new(100, 110, 80);
+        assert!(fg.contrast_ratio(&bg) < 4.5);
+        
+        let fixed = fg.with_min_contrast(&bg, 4.5);
+        assert!(fixed.contrast_ratio(&bg) >= 4.5);
+        // Only lightness moves, so the hue stays put
+        let (h0, _, _) = fg.to_hsl();
+        let (h1, _, _) = fixed.to_hsl();
+        assert!((h0 - h1).abs() < 5.0, "{} -> {}", h0, h1);
+    }
+    
+    #[test]
+    fn with_min_contrast_keeps_a_passing_pair() {
+        let fg = Rgb::new(0, 0, 0);
+        let bg = Rgb::new(255, 255, 255);
+        assert_eq!(fg.with_min_contrast(&bg, 7.0), fg);
+    }
+    
+    #[test]
+    fn adjust_brightness_reaches_white_and_black() {
+        let color = Rgb::new(34, 139, 34);
+        assert_eq!(color.adjust(1.0, 0.0), Rgb::new(255, 255, 255));
+        assert_eq!(color.adjust(-1.0, 0.0), Rgb::new(0, 0, 0));
+        
+        let light = color.adjust(0.95, 0.0);
+        let dark = color.adjust(-0.95, 0.0);
+        for c in [light.r, light.g, light.b] {
+            assert!(c >= 240, "{:?}", light);
+        }
+        for c in [dark.r, dark.g, dark.b] {
+            assert!(c <= 10, "{:?}", dark);
+        }
+        assert_eq!(color.adjust(0.0, 0.0), color);
+    }
+    
+    /// Primaries pick out one column of the Machado matrix, so these are
+    /// the published full-severity matrices passed through sRGB encoding
+    #[test]
+    fn cvd_primaries_match_machado_reference() {
+        let cases = [
+            (CvdMode::Protanopia, [(109, 95, 0), (255, 229, 0), (0, 89, 255)]),
+            (CvdMode::Deuteranopia, [(163, 144, 0), (239, 214, 58), (0, 61, 251)]),
+            (CvdMode::Tritanopia, [(255, 0, 15), (0, 247, 217), (0, 107, 150)]),
+        ];
+        let primaries = [Rgb::new(255, 0, 0), Rgb::new(0, 255, 0), Rgb::new(0, 0, 255)];
+        for (mode, expected) in cases {
+            for (primary, (r, g, b)) in primaries.iter().zip(expected) {
+                let got = primary.simulate_cvd(mode);
+                let close = |a: u8, b: u8| (a as i16 - b as i16).abs() <= 1;
+                assert!(close(got.r, r) && close(got.g, g) && close(got.b, b), "{:?} {:?} -> {:?}", mode, primary, got);
+            }
+        }
+        assert_eq!(Rgb::new(128, 128, 128).simulate_cvd(CvdMode::Deuteranopia), Rgb::new(128, 128, 128));
+    }
+    
+    #[test]
+    fn energy_aliases_max_energy() {
+        let mut stats = TurtleStats::default();
+        assert_eq!(stats.get("energy"), stats.get("max_energy"));
+        *stats.get_mut("energy").unwrap() = 120.0;
+        assert_eq!(stats.max_energy, 120.0);
+    }
 }